@@ -2,11 +2,11 @@
 extern crate afl;
 extern crate text_manipulation_rs;
 
-use std::convert::TryFrom;
+use text_manipulation_rs::text_generator::language::Language;
 
 fn main() {
     fuzz!(|data: &[u8]| {
-        let index = data.len() as i32;
-        text_manipulation_rs::text_generator::generate_text_for_language(index, false);
+        let language = Language::ALL[data.len() % Language::ALL.len()];
+        let _ = text_manipulation_rs::text_generator::generate_text_for_language(language, false);
     });
 }
@@ -0,0 +1,730 @@
+//! Command-line access to text generation, translation, dictionary lookups and DeepL glossaries.  Input is read from arguments or stdin, results are written to stdout, and errors go to stderr with a non-zero exit code, so the commands fit into shell pipelines.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+
+use text_manipulation_rs::casing::{self, CaseLocale};
+use text_manipulation_rs::deepl::{DeepLKey, Glossary, SourceLang, TargetLang};
+use text_manipulation_rs::dictionary::get_meaning;
+use text_manipulation_rs::my_memory::try_translate_q_langpair;
+use text_manipulation_rs::parallel::{build_parallel_corpus, ParallelFormat, ParallelOptions, Provider};
+use text_manipulation_rs::pseudo_locale::PseudoLocale;
+use text_manipulation_rs::request::glossary_request::{create_glossary_from_string, delete_glossary, get_glossaries, get_glossary, get_glossary_entries};
+use text_manipulation_rs::request::http_request::HttpResponseType;
+use text_manipulation_rs::request::translation_request::TranslationRequest;
+use text_manipulation_rs::text_generator::builder::{CorpusBuilder, SourceFormat};
+use text_manipulation_rs::text_generator::language::Language;
+use text_manipulation_rs::text_generator::readability::{Readability, ReadingLevel};
+use text_manipulation_rs::text_generator::resources::{generate_resources, ResourceOptions};
+use text_manipulation_rs::text_generator::stats::corpus_stats;
+use text_manipulation_rs::text_generator::{seeded_rng, stream, Corpus, ParagraphOptions};
+
+const USAGE: &str = "\
+Usage: text-manipulation <command> [options]
+
+Commands:
+  generate [--language CODE | --corpus PATH | --dir PATH] [--min-sentences N] [--max-bytes N] [--paragraphs N] [--seed N]
+           [--reading-level ease|grade|lix:MIN-MAX]
+      Generate placeholder text.  Use `--corpus -` to read the word list from stdin.  --reading-level aims for a
+      band of Flesch reading ease, Flesch–Kincaid grade or LIX, e.g. `grade:6-8`.
+  readability [TEXT]
+      Score TEXT, or stdin if no text is given: Flesch reading ease, Flesch–Kincaid grade, LIX, words per
+      sentence and syllables per word.
+  stats [--language CODE | --corpus PATH | --dir PATH]
+      Report on a word list: blank, padded and duplicate lines, word lengths, scripts and mixed-script words.
+  build-corpus [--format text|markdown|html] [--min-count N] [--max-words N] [--stopwords FILE] [--keep numbers,urls,case] [--output PATH] [PATH...]
+      Count the words of documents and write a word list ranked by frequency.  Directories are read recursively
+      and each file's format follows its extension.  Reads stdin, as --format, if no path is given.
+  resources --output DIR [--languages CODE,...] [--expansion PERCENT] [--seed N] SOURCE
+      Write a copy of a JSON, YAML, .po or .ftl resource file for each language, every value replaced by
+      generated text of a similar length.  Placeholders are kept.  Defaults to every bundled language.
+  parallel --to LANG [--language CODE] [--sentences N] [--provider deepl|mymemory|pseudo,...] [--glossary ID]
+           [--format tsv|jsonl|tmx] [--output PATH] [--seed N]
+      Generate sentences and translate each one into aligned pairs.  Providers are tried in order for each
+      sentence, e.g. `--provider deepl,mymemory` falls back to MyMemory.  The format follows --output's extension.
+  case --to lower|upper|title|sentence [--locale TAG] [TEXT]
+      Change the case of TEXT, or stdin if no text is given, with the rules of a language such as tr, nl or el.
+  translate --to LANG [--from LANG] [--provider deepl|mymemory|pseudo] [--glossary ID] [TEXT]
+      Translate TEXT, or stdin if no text is given.  MyMemory needs --from.  The pseudo provider
+      pseudo-localizes each line offline; `--to ar-XB` also mirrors it right to left.
+  define WORD
+      Look up the meanings of a word.  Reads the key from dict_secret.txt.
+  glossary create --name NAME --from LANG --to LANG [FILE]
+      Create a DeepL glossary from tab-separated entries in FILE or stdin.
+  glossary list
+  glossary show ID
+  glossary entries ID
+  glossary delete ID
+
+Options:
+  --json            Write the result as JSON
+  --key-file PATH   File holding the DeepL API key, instead of the DEEPL_AUTH_KEY environment variable
+  -h, --help        Show this message
+
+Exit codes: 0 on success, 1 if the command failed, 2 if the command line is invalid.";
+
+/// Why a command did not succeed.
+enum CliError {
+    /// The command line is invalid
+    Usage(String),
+
+    /// The command was understood but failed, e.g. a request was rejected
+    Failed(Box<dyn Error>),
+}
+
+impl<E: Into<Box<dyn Error>>> From<E> for CliError {
+    fn from(e: E) -> Self {
+        CliError::Failed(e.into())
+    }
+}
+
+fn usage<T>(message: impl Into<String>) -> Result<T, CliError> {
+    Err(CliError::Usage(message.into()))
+}
+
+/// A parsed command line: positional arguments, `--flag value` pairs and the `--json` and `--help` switches.
+struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+    json: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse(raw: impl Iterator<Item = String>) -> Result<Args, CliError> {
+        let mut args = Args { positional: Vec::new(), flags: HashMap::new(), json: false, help: false };
+        let is_switch = |arg: &str| matches!(arg, "--json" | "--help" | "-h");
+
+        // switches take no value, so they are never read as the value of the flag before them
+        let mut raw = raw.peekable();
+        while let Some(arg) = raw.next() {
+            if arg == "--json" {
+                args.json = true;
+            } else if arg == "--help" || arg == "-h" {
+                args.help = true;
+            } else if let Some(flag) = arg.strip_prefix("--") {
+                let (name, value) = match flag.split_once('=') {
+                    Some((name, value)) => (name.to_owned(), value.to_owned()),
+                    None => match raw.next_if(|value| !is_switch(value)) {
+                        Some(value) => (flag.to_owned(), value),
+                        None if raw.any(|a| a == "--help" || a == "-h") => return Ok(Args { help: true, ..args }),
+                        None => return usage(format!("--{} needs a value", flag))
+                    }
+                };
+                args.flags.insert(name, value);
+            } else {
+                args.positional.push(arg);
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Fail if any flag is not in the list a command accepts.
+    fn allow(&self, allowed: &[&str]) -> Result<(), CliError> {
+        match self.flags.keys().find(|f| !allowed.contains(&f.as_str())) {
+            Some(f) => usage(format!("unknown option --{}", f)),
+            None => Ok(())
+        }
+    }
+
+    fn flag(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(|v| v.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        match self.flag(name) {
+            Some(v) => Ok(v),
+            None => usage(format!("--{} is required", name))
+        }
+    }
+
+    fn number<T: FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        match self.flag(name) {
+            Some(v) => match v.parse() {
+                Ok(n) => Ok(Some(n)),
+                Err(_) => usage(format!("--{} must be a number, got \"{}\"", name, v))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// The positional arguments joined by spaces, or stdin if there are none.
+    fn text_or_stdin(&self) -> Result<String, CliError> {
+        if !self.positional.is_empty() {
+            return Ok(self.positional.join(" "));
+        }
+
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(text.trim_end_matches(['\n', '\r']).to_owned())
+    }
+
+    /// The single positional argument a command works on.
+    fn one(&self, what: &str) -> Result<&str, CliError> {
+        match self.positional.as_slice() {
+            [value] => Ok(value),
+            _ => usage(format!("expected exactly one {}", what))
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut raw = std::env::args().skip(1);
+    let command = raw.next();
+    match command.as_deref() {
+        Some("-h") | Some("--help") | Some("help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+        _ => ()
+    }
+
+    let result = Args::parse(raw).and_then(|args| match command.as_deref() {
+        _ if args.help => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        Some("generate") => generate(&args),
+        Some("readability") => readability(&args),
+        Some("stats") => stats(&args),
+        Some("build-corpus") => build_corpus(&args),
+        Some("resources") => resources(&args),
+        Some("parallel") => parallel(&args),
+        Some("case") => case(&args),
+        Some("translate") => translate(&args),
+        Some("define") => define(&args),
+        Some("glossary") => glossary(&args),
+        Some(other) => usage(format!("unknown command \"{}\"", other)),
+        None => unreachable!()
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\nRun `text-manipulation --help` for usage.", message);
+            ExitCode::from(2)
+        },
+        Err(CliError::Failed(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Print a value as pretty JSON.
+fn print_json(value: &Value) -> Result<(), CliError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// The corpus chosen with `--language`, `--corpus` or `--dir`, if any, and its language.  `--corpus -` reads the word list from stdin.
+fn corpus(args: &Args) -> Result<(Option<Corpus<'_>>, Option<Language>), CliError> {
+    let language = match args.flag("language") {
+        Some(code) => match Language::from_str(code) {
+            Ok(l) => Some(l),
+            Err(e) => return usage(e.to_string())
+        },
+        None => None
+    };
+    let corpus = match (language, args.flag("corpus"), args.flag("dir")) {
+        (Some(l), None, None) => Corpus::Embedded(l),
+        (None, Some("-"), None) => Corpus::FromReader(Box::new(io::stdin())),
+        (None, Some(path), None) => Corpus::FromFile(path),
+        (None, None, Some(path)) => Corpus::FromDir(path),
+        (None, None, None) => return Ok((None, None)),
+        _ => return usage("use only one of --language, --corpus and --dir")
+    };
+
+    Ok((Some(corpus), language))
+}
+
+fn generate(args: &Args) -> Result<(), CliError> {
+    args.allow(&["language", "corpus", "dir", "min-sentences", "max-bytes", "paragraphs", "seed", "reading-level"])?;
+    if !args.positional.is_empty() {
+        return usage("generate takes no positional arguments");
+    }
+
+    let (corpus, language) = corpus(args)?;
+    let corpus = corpus.unwrap_or(Corpus::Embedded(Language::En));
+
+    let mut options = ParagraphOptions::new();
+    if let Some(n) = args.number("min-sentences")? {
+        options = options.set_min_sentences(n);
+    }
+    if let Some(n) = args.number("max-bytes")? {
+        options = options.set_max_bytes(n);
+    }
+    if let Some(spec) = args.flag("reading-level") {
+        options = options.set_reading_level(reading_level(spec)?);
+    }
+    let count = args.number("paragraphs")?.unwrap_or(1);
+    let seed = match args.number("seed")? {
+        Some(seed) => seed,
+        None => thread_rng().gen()
+    };
+
+    let paragraphs: Vec<String> = stream::paragraphs(corpus, &options, seeded_rng(seed))?.take(count).collect();
+
+    if args.json {
+        print_json(&json!({
+            "language": language.map(|l| l.code()),
+            "seed": seed,
+            "paragraphs": paragraphs,
+        }))
+    } else {
+        println!("{}", paragraphs.join("\n\n"));
+        Ok(())
+    }
+}
+
+/// A reading level written as `SCALE:MIN-MAX`, e.g. `ease:60-70`, `grade:6-8` or `lix:30-40`.
+fn reading_level(spec: &str) -> Result<ReadingLevel, CliError> {
+    let invalid = || usage(format!("--reading-level takes ease, grade or lix and a range, e.g. grade:6-8, got \"{}\"", spec));
+    let Some((scale, range)) = spec.split_once(':') else {
+        return invalid();
+    };
+    // the range is split on its last dash, so a minimum may be negative
+    let bounds = range.char_indices().skip(1).filter(|&(_, c)| c == '-').last()
+        .and_then(|(i, _)| Some((range[..i].trim().parse().ok()?, range[i + 1..].trim().parse().ok()?)));
+    let Some((min, max)) = bounds else {
+        return invalid();
+    };
+    match scale {
+        "ease" | "flesch" => Ok(ReadingLevel::FleschReadingEase { min, max }),
+        "grade" | "kincaid" => Ok(ReadingLevel::FleschKincaidGrade { min, max }),
+        "lix" => Ok(ReadingLevel::Lix { min, max }),
+        _ => invalid()
+    }
+}
+
+fn readability(args: &Args) -> Result<(), CliError> {
+    args.allow(&[])?;
+    let readability = Readability::of(&args.text_or_stdin()?);
+
+    if args.json {
+        print_json(&json!({
+            "sentences": readability.sentences,
+            "words": readability.words,
+            "syllables": readability.syllables,
+            "long_words": readability.long_words,
+            "average_sentence_length": readability.average_sentence_length(),
+            "syllables_per_word": readability.syllables_per_word(),
+            "flesch_reading_ease": readability.flesch_reading_ease(),
+            "flesch_kincaid_grade": readability.flesch_kincaid_grade(),
+            "lix": readability.lix(),
+        }))
+    } else {
+        println!("{}", readability);
+        Ok(())
+    }
+}
+
+fn case(args: &Args) -> Result<(), CliError> {
+    args.allow(&["to", "locale"])?;
+    let locale = args.flag("locale").map_or(CaseLocale::Root, CaseLocale::from_tag);
+    let convert = match args.required("to")? {
+        "lower" => casing::to_lower,
+        "upper" => casing::to_upper,
+        "title" => casing::to_title,
+        "sentence" => casing::to_sentence,
+        other => return usage(format!("--to takes lower, upper, title or sentence, got \"{}\"", other))
+    };
+    let text = convert(&args.text_or_stdin()?, locale);
+
+    if args.json {
+        print_json(&json!({ "locale": locale, "text": text }))
+    } else {
+        println!("{}", text);
+        Ok(())
+    }
+}
+
+fn stats(args: &Args) -> Result<(), CliError> {
+    args.allow(&["language", "corpus", "dir"])?;
+    if !args.positional.is_empty() {
+        return usage("stats takes no positional arguments");
+    }
+
+    let corpus = match corpus(args)? {
+        (Some(corpus), _) => corpus,
+        (None, _) => return usage("stats needs --language, --corpus or --dir")
+    };
+    let stats = corpus_stats(corpus)?;
+
+    if args.json {
+        print_json(&json!(stats))
+    } else {
+        println!("{}", stats);
+        Ok(())
+    }
+}
+
+fn build_corpus(args: &Args) -> Result<(), CliError> {
+    args.allow(&["format", "min-count", "max-words", "stopwords", "keep", "output"])?;
+
+    let mut builder = CorpusBuilder::new();
+    if let Some(n) = args.number("min-count")? {
+        builder = builder.set_min_count(n);
+    }
+    if let Some(n) = args.number("max-words")? {
+        builder = builder.set_max_words(n);
+    }
+    if let Some(path) = args.flag("stopwords") {
+        let stopwords = std::fs::read_to_string(path)?;
+        builder = builder.set_stopwords(&stopwords.lines().collect::<Vec<&str>>());
+    }
+    for kept in args.flag("keep").into_iter().flat_map(|k| k.split(',')) {
+        builder = match kept.trim() {
+            "numbers" => builder.set_keep_numbers(true),
+            "urls" => builder.set_keep_urls(true),
+            "case" => builder.set_lowercase(false),
+            other => return usage(format!("--keep takes numbers, urls or case, got \"{}\"", other))
+        };
+    }
+
+    if args.positional.is_empty() {
+        let format = match args.flag("format").unwrap_or("text") {
+            "text" => SourceFormat::Text,
+            "markdown" | "md" => SourceFormat::Markdown,
+            "html" => SourceFormat::Html,
+            other => return usage(format!("unknown format \"{}\"", other))
+        };
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        builder.add_document(&text, format);
+    } else {
+        if args.flag("format").is_some() {
+            return usage("--format only applies to stdin; files are read by their extension");
+        }
+        for path in &args.positional {
+            if std::path::Path::new(path).is_dir() {
+                builder.add_dir(path)?;
+            } else {
+                builder.add_file(path)?;
+            }
+        }
+    }
+
+    let ranked = builder.ranked();
+    match (args.flag("output"), args.json) {
+        (Some(path), _) => {
+            builder.write(path)?;
+            eprintln!("Wrote {} words to {}", ranked.len(), path);
+            Ok(())
+        },
+        (None, true) => print_json(&json!(ranked.iter().map(|(w, n)| json!({ "word": w, "count": n })).collect::<Vec<Value>>())),
+        (None, false) => {
+            print!("{}", builder.to_word_list());
+            Ok(())
+        }
+    }
+}
+
+fn resources(args: &Args) -> Result<(), CliError> {
+    args.allow(&["output", "languages", "expansion", "seed"])?;
+    let source = args.one("source file")?;
+    let output = args.required("output")?;
+
+    let mut options = ResourceOptions::new();
+    if let Some(codes) = args.flag("languages") {
+        let mut languages = Vec::new();
+        for code in codes.split(',') {
+            match Language::from_str(code.trim()) {
+                Ok(l) => languages.push(l),
+                Err(e) => return usage(e.to_string())
+            }
+        }
+        options = options.set_languages(&languages);
+    }
+    if let Some(percent) = args.number("expansion")? {
+        options = options.set_expansion(percent);
+    }
+    let seed = match args.number("seed")? {
+        Some(seed) => seed,
+        None => thread_rng().gen()
+    };
+
+    let paths = generate_resources(source, output, &options, seed)?;
+    if args.json {
+        print_json(&json!({
+            "seed": seed,
+            "files": paths.iter().map(|p| p.display().to_string()).collect::<Vec<String>>(),
+        }))
+    } else {
+        for path in &paths {
+            println!("{}", path.display());
+        }
+        Ok(())
+    }
+}
+
+fn parallel(args: &Args) -> Result<(), CliError> {
+    args.allow(&["to", "language", "sentences", "provider", "glossary", "format", "output", "seed", "key-file"])?;
+    if !args.positional.is_empty() {
+        return usage("parallel takes no positional arguments");
+    }
+
+    let mut options = ParallelOptions::new().set_target(args.required("to")?);
+    if let Some(code) = args.flag("language") {
+        match Language::from_str(code) {
+            Ok(l) => options = options.set_source(l),
+            Err(e) => return usage(e.to_string())
+        }
+    }
+    if let Some(n) = args.number("sentences")? {
+        options = options.set_sentences(n);
+    }
+
+    let mut providers = Vec::new();
+    for name in args.flag("provider").unwrap_or("deepl").split(',') {
+        providers.push(match name.trim() {
+            "deepl" => Provider::DeepL { key: deepl_key(args)?, glossary: args.flag("glossary").map(|g| g.to_owned()) },
+            "mymemory" => Provider::MyMemory,
+            "pseudo" => Provider::Pseudo,
+            other => return usage(format!("unknown provider \"{}\"", other))
+        });
+    }
+    if args.flag("glossary").is_some() && !providers.iter().any(|p| matches!(p, Provider::DeepL { .. })) {
+        return usage("glossaries are only supported by DeepL");
+    }
+
+    let format = match (args.flag("format"), args.flag("output")) {
+        (Some("tsv"), _) => ParallelFormat::Tsv,
+        (Some("jsonl"), _) => ParallelFormat::Jsonl,
+        (Some("tmx"), _) => ParallelFormat::Tmx,
+        (Some(other), _) => return usage(format!("unknown format \"{}\"", other)),
+        (None, Some(path)) => ParallelFormat::from_path(path).unwrap_or_default(),
+        (None, None) => ParallelFormat::Tsv
+    };
+    let seed = match args.number("seed")? {
+        Some(seed) => seed,
+        None => thread_rng().gen()
+    };
+
+    let corpus = build_parallel_corpus(&providers, &options, seed)?;
+    match args.flag("output") {
+        Some(path) => {
+            corpus.write(path, format)?;
+            eprintln!("Wrote {} sentence pairs to {}", corpus.pairs.len(), path);
+        },
+        None => print!("{}", corpus.render(format))
+    }
+    Ok(())
+}
+
+/// Read the DeepL API key from `--key-file`, or from the DEEPL_AUTH_KEY environment variable.
+fn deepl_key(args: &Args) -> Result<DeepLKey, CliError> {
+    let key = match args.flag("key-file") {
+        Some(path) => DeepLKey::new(path)?.key,
+        None => match std::env::var("DEEPL_AUTH_KEY") {
+            Ok(key) => key,
+            Err(_) => return usage("a DeepL API key is needed: pass --key-file or set DEEPL_AUTH_KEY")
+        }
+    };
+
+    Ok(DeepLKey { key: key.trim().to_owned() })
+}
+
+fn source_lang(code: &str) -> Result<SourceLang, CliError> {
+    match SourceLang::from_str(&code.to_uppercase()) {
+        Ok(l) => Ok(l),
+        Err(()) => usage(format!("unsupported source language \"{}\"", code))
+    }
+}
+
+fn target_lang(code: &str) -> Result<TargetLang, CliError> {
+    match TargetLang::from_str(&code.to_uppercase()) {
+        Ok(l) => Ok(l),
+        Err(()) => usage(format!("unsupported target language \"{}\"", code))
+    }
+}
+
+fn translate(args: &Args) -> Result<(), CliError> {
+    args.allow(&["to", "from", "provider", "glossary", "key-file"])?;
+    let to = args.required("to")?;
+    let from = args.flag("from");
+    let provider = args.flag("provider").unwrap_or("deepl");
+
+    let translation = match provider {
+        "deepl" => {
+            let target = target_lang(to)?;
+            let source = from.map(source_lang).transpose()?;
+            let auth = deepl_key(args)?;
+            let text = args.text_or_stdin()?;
+
+            let mut request = TranslationRequest::new(&text, target);
+            if let Some(sl) = source {
+                request = request.set_source_lang(sl);
+            }
+            if let Some(id) = args.flag("glossary") {
+                request = request.set_glossary_id(id);
+            }
+
+            let translated = match request.create_request(&auth).execute()? {
+                HttpResponseType::Json(j) => j["translations"][0]["text"].as_str().map(|t| t.to_owned()),
+                HttpResponseType::Tsv(_) => None
+            };
+            let translated = translated.ok_or("DeepL returned no translation")?;
+            (text, translated)
+        },
+        "mymemory" => {
+            if args.flag("glossary").is_some() {
+                return usage("glossaries are only supported by DeepL");
+            }
+            let from = match from {
+                Some(from) => from,
+                None => return usage("--from is required with MyMemory")
+            };
+            let text = args.text_or_stdin()?;
+            let translated = try_translate_q_langpair(text.clone(), format!("{}|{}", from, to))?;
+            (text, translated)
+        },
+        "pseudo" => {
+            if args.flag("glossary").is_some() {
+                return usage("glossaries are only supported by DeepL");
+            }
+            let pseudo = PseudoLocale::new().set_mirror(to.eq_ignore_ascii_case("ar-XB"));
+            let text = args.text_or_stdin()?;
+            let translated: Vec<String> = text.lines().map(|line| pseudo.transform(line)).collect();
+            (text, translated.join("\n"))
+        },
+        other => return usage(format!("unknown provider \"{}\"", other))
+    };
+
+    let (text, translated) = translation;
+    if args.json {
+        print_json(&json!({
+            "provider": provider,
+            "from": from,
+            "to": to,
+            "text": text,
+            "translation": translated,
+        }))
+    } else {
+        println!("{}", translated);
+        Ok(())
+    }
+}
+
+fn define(args: &Args) -> Result<(), CliError> {
+    args.allow(&[])?;
+    let word = args.one("word")?;
+    let definitions = get_meaning(word)?;
+
+    if args.json {
+        print_json(&json!({ "word": word, "definitions": definitions }))
+    } else {
+        for definition in definitions {
+            println!("{}", definition);
+        }
+        Ok(())
+    }
+}
+
+/// A glossary as JSON.  `Glossary` keeps its name and creation time JSON-encoded, so they are decoded back into plain strings.
+fn glossary_json(g: &Glossary) -> Value {
+    let decode = |s: &str| serde_json::from_str::<Value>(s).unwrap_or_else(|_| Value::String(s.to_owned()));
+    json!({
+        "glossary_id": g.glossary_id,
+        "name": decode(&g.name),
+        "ready": g.ready,
+        "source_lang": g.source_lang.to_string(),
+        "target_lang": g.target_lang.to_string(),
+        "creation_time": decode(&g.creation_time),
+        "entry_count": g.entry_count,
+    })
+}
+
+fn glossary(args: &Args) -> Result<(), CliError> {
+    let action = match args.positional.first() {
+        Some(action) => action.as_str(),
+        None => return usage("glossary needs an action: create, list, show, entries or delete")
+    };
+    let rest = Args { positional: args.positional[1..].to_vec(), flags: args.flags.clone(), json: args.json, help: false };
+
+    match action {
+        "create" => {
+            rest.allow(&["name", "from", "to", "key-file"])?;
+            let name = rest.required("name")?.to_owned();
+            let source = source_lang(rest.required("from")?)?;
+            let target = target_lang(rest.required("to")?)?;
+            let auth = deepl_key(&rest)?;
+            let entries = match rest.positional.as_slice() {
+                [] => rest.text_or_stdin()?,
+                [path] => std::fs::read_to_string(path)?,
+                _ => return usage("expected at most one entries file")
+            };
+
+            let created = create_glossary_from_string(&auth, name, source, target, entries)?;
+            if rest.json {
+                print_json(&created)
+            } else {
+                println!("{}", created["glossary_id"].as_str().unwrap_or_default());
+                Ok(())
+            }
+        },
+        "list" => {
+            rest.allow(&["key-file"])?;
+            if !rest.positional.is_empty() {
+                return usage("glossary list takes no arguments");
+            }
+            let glossaries = get_glossaries(&deepl_key(&rest)?)?;
+            if rest.json {
+                print_json(&Value::Array(glossaries.iter().map(glossary_json).collect()))
+            } else {
+                let shown: Vec<String> = glossaries.iter().map(|g| g.to_string()).collect();
+                println!("{}", shown.join("\n\n"));
+                Ok(())
+            }
+        },
+        "show" => {
+            rest.allow(&["key-file"])?;
+            let id = rest.one("glossary ID")?;
+            let glossary = get_glossary(&deepl_key(&rest)?, id.to_owned())?;
+            if rest.json {
+                print_json(&glossary_json(&glossary))
+            } else {
+                println!("{}", glossary);
+                Ok(())
+            }
+        },
+        "entries" => {
+            rest.allow(&["key-file"])?;
+            let id = rest.one("glossary ID")?;
+            let entries = get_glossary_entries(&deepl_key(&rest)?, id.to_owned())?;
+            let mut sorted: Vec<(&String, &String)> = entries.iter().collect();
+            sorted.sort();
+            if rest.json {
+                // a HashMap iterates in a different order on each run
+                print_json(&json!(entries.iter().collect::<BTreeMap<&String, &String>>()))
+            } else {
+                for (source, target) in sorted {
+                    println!("{}\t{}", source, target);
+                }
+                Ok(())
+            }
+        },
+        "delete" => {
+            rest.allow(&["key-file"])?;
+            let id = rest.one("glossary ID")?;
+            delete_glossary(&deepl_key(&rest)?, id.to_owned())?;
+            if rest.json {
+                print_json(&json!({ "deleted": id }))
+            } else {
+                Ok(())
+            }
+        },
+        other => usage(format!("unknown glossary action \"{}\"", other))
+    }
+}
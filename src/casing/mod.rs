@@ -0,0 +1,155 @@
+//! Full Unicode lower, upper, title and sentence casing with language-specific rules.  Unlike ASCII casing, "ÉCOLE" lowercases to "école", "straße" uppercases to "STRASSE" and Greek final sigma becomes "ς".
+//!
+//! Some languages change case differently, so each function takes a `CaseLocale`.  Turkish and Azerbaijani keep dotted "İ/i" and dotless "I/ı" apart, Dutch title-cases "ij" as "IJ", and Greek drops accents in uppercase.  Title casing also uses the title forms of digraphs and ligatures, so "ǆ" becomes "ǅ" rather than "Ǆ" and "ﬁ" becomes "Fi".
+
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text_generator::language::Language;
+
+/// The casing rules of a language.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+pub enum CaseLocale {
+    /// The Unicode default rules, right for most languages
+    #[default]
+    Root,
+
+    /// Turkish, Azerbaijani, Tatar and Bashkir: "i" uppercases to "İ" and "I" lowercases to "ı"
+    Turkic,
+
+    /// Dutch: "ij" is title-cased as one letter, as in "IJsselmeer"
+    Dutch,
+
+    /// Greek: accents and breathings are dropped in uppercase, as in "ΑΘΗΝΑ"
+    Greek,
+}
+
+impl CaseLocale {
+    /// The rules for a BCP 47 language tag such as "tr", "nl-BE" or "el_GR".  Unknown languages use the default rules.
+    pub fn from_tag(tag: &str) -> CaseLocale {
+        let language = tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        match language.as_str() {
+            "tr" | "az" | "tt" | "ba" | "crh" => CaseLocale::Turkic,
+            "nl" => CaseLocale::Dutch,
+            "el" => CaseLocale::Greek,
+            _ => CaseLocale::Root
+        }
+    }
+}
+
+impl From<Language> for CaseLocale {
+    fn from(language: Language) -> Self {
+        CaseLocale::from_tag(language.code())
+    }
+}
+
+/// Lowercase a text.
+pub fn to_lower(text: &str, locale: CaseLocale) -> String {
+    match locale {
+        CaseLocale::Turkic => {
+            // a dot above after "I" belongs to the letter, not the lowercase "i"
+            let text = text.replace("I\u{307}", "i").replace('I', "ı").replace('İ', "i");
+            text.to_lowercase()
+        },
+        _ => text.to_lowercase(),
+    }
+}
+
+/// Uppercase a text.
+pub fn to_upper(text: &str, locale: CaseLocale) -> String {
+    match locale {
+        CaseLocale::Turkic => text.replace('i', "İ").to_uppercase(),
+        CaseLocale::Greek => strip_greek_accents(&text.to_uppercase()),
+        _ => text.to_uppercase(),
+    }
+}
+
+/// Title-case every word: its first letter in title case and the rest in lowercase.
+pub fn to_title(text: &str, locale: CaseLocale) -> String {
+    text.split_word_bounds()
+        .map(|word| match word.chars().any(char::is_alphabetic) {
+            true => title_first(&to_lower(word, locale), locale),
+            false => word.to_owned(),
+        })
+        .collect()
+}
+
+/// Lowercase a text, then capitalize its first letter.
+pub fn to_sentence(text: &str, locale: CaseLocale) -> String {
+    capitalize_first(&to_lower(text, locale), locale)
+}
+
+/// Capitalize the first letter of a text, skipping opening quotes and brackets, and leave the rest as it is.  Nothing changes if the text starts with a number.
+pub fn capitalize_first(text: &str, locale: CaseLocale) -> String {
+    match text.chars().find(|c| c.is_alphanumeric()) {
+        Some(c) if c.is_alphabetic() => title_first(text, locale),
+        _ => text.to_owned(),
+    }
+}
+
+/// Title-case the first letter of a text.
+fn title_first(text: &str, locale: CaseLocale) -> String {
+    let Some((start, first)) = text.char_indices().find(|(_, c)| c.is_alphabetic()) else {
+        return text.to_owned();
+    };
+    let rest = &text[start + first.len_utf8()..];
+
+    let (title, rest) = match (locale, first) {
+        (CaseLocale::Dutch, 'i') if rest.starts_with(['j', 'J']) => (String::from("IJ"), &rest[1..]),
+        (CaseLocale::Turkic, 'i') => (String::from("İ"), rest),
+        _ => (to_title_char(first), rest),
+    };
+    format!("{}{}{}", &text[..start], title, rest)
+}
+
+/// The title case of a letter.  It differs from the uppercase for digraphs, ligatures and Greek letters with iota subscript.
+fn to_title_char(c: char) -> String {
+    let title = match c {
+        'Ǆ'..='ǆ' => "ǅ",
+        'Ǉ'..='ǉ' => "ǈ",
+        'Ǌ'..='ǌ' => "ǋ",
+        'Ǳ'..='ǳ' => "ǲ",
+        'ß' => "Ss",
+        'ﬀ' => "Ff",
+        'ﬁ' => "Fi",
+        'ﬂ' => "Fl",
+        'ﬃ' => "Ffi",
+        'ﬄ' => "Ffl",
+        'ﬅ' | 'ﬆ' => "St",
+        'և' => "Եւ",
+        'ᾳ' => "ᾼ",
+        'ῃ' => "ῌ",
+        'ῳ' => "ῼ",
+        // the iota subscript forms of alpha, eta and omega with breathings
+        '\u{1F80}'..='\u{1F87}' | '\u{1F90}'..='\u{1F97}' | '\u{1FA0}'..='\u{1FA7}' => {
+            return char::from_u32(c as u32 + 8).map(String::from).unwrap_or_default();
+        },
+        '\u{1F88}'..='\u{1F8F}' | '\u{1F98}'..='\u{1F9F}' | '\u{1FA8}'..='\u{1FAF}' | 'ᾼ' | 'ῌ' | 'ῼ' => return c.to_string(),
+        _ => return c.to_uppercase().collect(),
+    };
+    title.to_owned()
+}
+
+/// Remove the accents and breathings of Greek letters.  Diaeresis is kept, since it changes how a vowel is read.
+fn strip_greek_accents(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut greek = false;
+    for c in text.nfd() {
+        match c {
+            '\u{300}' | '\u{301}' | '\u{342}' | '\u{313}' | '\u{314}' if greek => (),
+            '\u{345}' if greek => stripped.push('Ι'),
+            _ => {
+                if !is_combining(c) {
+                    greek = ('\u{370}'..='\u{3FF}').contains(&c) || ('\u{1F00}'..='\u{1FFF}').contains(&c);
+                }
+                stripped.push(c);
+            },
+        }
+    }
+    stripped.nfc().collect()
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36F}')
+}
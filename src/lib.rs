@@ -1,7 +1,10 @@
+pub mod casing;
 pub mod deepl;
 pub mod request;
 pub mod dictionary;
 pub mod my_memory;
+pub mod parallel;
+pub mod pseudo_locale;
 pub mod text_generator;
 
 pub mod text_manipulation{
@@ -18,6 +21,7 @@ pub mod text_manipulation{
         use crate::dictionary::get_meaning;
         use crate::my_memory::translate_q_langpair;
         use crate::text_generator::*;
+        use crate::text_generator::language::{Language, UnknownLanguage};
         
         fn get_auth() -> DeepLKey {
             DeepLKey::new("src/secret.txt").unwrap()
@@ -45,6 +49,785 @@ pub mod text_manipulation{
             assert!(!paragraph.is_empty());
         }
 
+        #[test]
+        // Test that an order-1 chain only produces word pairs seen in the training text
+        fn test_markov_chain_transitions() {
+            let text = "the cat sat on the mat. the dog sat on the rug! a bird sang";
+            let chain = markov::MarkovChain::train(text, 1);
+            let mut rng = rand::thread_rng();
+
+            for _ in 0..20 {
+                let sentence = chain.generate_sentence(&mut rng);
+                assert!(!sentence.is_empty());
+                assert!(["the", "a"].contains(&sentence[0].as_str()));
+                for pair in sentence.windows(2) {
+                    assert!(text.contains(&pair.join(" ")));
+                }
+            }
+        }
+
+        #[test]
+        // Test that a Markov corpus falls back to the word list when there is no training text
+        fn test_markov_fallback() {
+            let corpus = Corpus::Markov { training: "corpus/missing.txt", order: 2, fallback: "corpus/english.txt" };
+            let paragraph = generate_paragraph(corpus, None, None);
+            assert!(paragraph.contains('.'));
+        }
+
+        #[test]
+        // Test that the same seed reproduces the same paragraph and a different seed does not
+        fn test_seeded_generation() {
+            let options = ParagraphOptions::new().set_min_sentences(4);
+            let first = generate_paragraph_from_seed(Corpus::FromFile("corpus/english.txt"), &options, 42).unwrap();
+            let second = generate_paragraph_from_seed(Corpus::FromFile("corpus/english.txt"), &options, 42).unwrap();
+            let other = generate_paragraph_from_seed(Corpus::FromFile("corpus/english.txt"), &options, 43).unwrap();
+            assert_eq!(first, second);
+            assert_ne!(first, other);
+
+            let mut rng = seeded_rng(42);
+            let with_rng = generate_paragraph_with_rng(Corpus::FromFile("corpus/english.txt"), &options, &mut rng).unwrap();
+            assert_eq!(first, with_rng);
+        }
+
+        #[test]
+        // Test that every language parses from its code and generates from its embedded corpus
+        fn test_language_codes() {
+            for language in Language::ALL {
+                assert_eq!(language.code().parse::<Language>(), Ok(language));
+                assert!(path::Path::new(&format!("corpus/{}.txt", language.name())).exists());
+                assert!(!generate_paragraph(Corpus::Embedded(language), None, None).is_empty());
+            }
+            assert_eq!("SV".parse::<Language>(), Ok(Language::Sv));
+            assert_eq!("xx".parse::<Language>(), Err(UnknownLanguage(String::from("xx"))));
+        }
+
+        #[test]
+        // Test that sentences follow the conventions of their script
+        fn test_script_typography() {
+            let japanese = generate_paragraph(Corpus::Embedded(Language::Ja), None, None);
+            assert!(japanese.ends_with('。'));
+            assert!(!japanese.contains(' ') && !japanese.contains('.'));
+
+            let hindi = generate_paragraph(Corpus::Embedded(Language::Hi), None, None);
+            assert!(hindi.ends_with('।'));
+            assert!(!hindi.contains('.'));
+
+            let arabic = Language::Ar.typography();
+            assert_eq!(arabic.direction, typography::Direction::Rtl);
+            assert_eq!(arabic.question_mark, "؟");
+
+            // A word list read from a file can borrow the conventions of a language
+            let options = ParagraphOptions::new().set_typography(Language::Ja.typography());
+            let paragraph = generate_paragraph_from_seed(Corpus::FromFile("corpus/japanese.txt"), &options, 7).unwrap();
+            assert!(paragraph.ends_with('。'));
+        }
+
+        #[test]
+        // Test that the sentence and paragraph iterators and the reader stream text on demand
+        fn test_streaming_generation() {
+            use std::io::Read;
+
+            let options = ParagraphOptions::new();
+            let sentences = stream::sentences(Corpus::Embedded(Language::En), &options, seeded_rng(1)).unwrap();
+            assert!(sentences.take(1000).all(|s| s.ends_with('.')));
+
+            let paragraphs = stream::paragraphs(Corpus::Embedded(Language::En), &options, seeded_rng(1)).unwrap();
+            let first_two: Vec<String> = paragraphs.take(2).collect();
+
+            let reader = stream::paragraphs(Corpus::Embedded(Language::En), &options, seeded_rng(1)).unwrap().into_reader();
+            let mut text = String::new();
+            reader.take(3_000_000).read_to_string(&mut text).unwrap();
+            assert_eq!(text.len(), 3_000_000);
+            assert!(text.starts_with(&format!("{}\n\n{}\n\n", first_two[0], first_two[1])));
+
+            assert!(stream::sentences(Corpus::FromFile("corpus/missing.txt"), &options, seeded_rng(1)).is_err());
+        }
+
+        #[test]
+        // Test that a document renders to Markdown, HTML and plain text with all of its parts
+        fn test_document_generation() {
+            use crate::text_generator::document::{generate_document_from_seed, Block, DocumentOptions, Inline};
+
+            let options = DocumentOptions::new()
+                .set_sections(2)
+                .set_list_probability(1.0)
+                .set_quote_probability(1.0)
+                .set_link_probability(1.0);
+            let document = generate_document_from_seed(Corpus::Embedded(Language::En), &options, 3).unwrap();
+            assert_eq!(document.sections.len(), 2);
+
+            let markdown = document.to_markdown();
+            assert!(markdown.starts_with(&format!("# {}\n", document.title)));
+            assert!(markdown.contains("\n## ") && markdown.contains("\n> "));
+            assert!(markdown.contains("](https://example.com/page-1)"));
+
+            let html = document.to_html();
+            assert!(html.contains("<html lang=\"en\">") && html.contains("<blockquote>"));
+            assert!(html.contains("<ul>") || html.contains("<ol>"));
+            assert!(html.contains("<a href=\"https://example.com/page-1\">"));
+
+            let plain = document.to_plain_text();
+            assert!(plain.starts_with(&document.title));
+            assert!(!plain.contains("https://"));
+
+            let arabic = generate_document_from_seed(Corpus::Embedded(Language::Ar), &options, 3).unwrap();
+            assert!(arabic.to_html().contains("<html lang=\"ar\" dir=\"rtl\">"));
+
+            let nan = DocumentOptions::new().set_list_probability(f64::NAN).set_quote_probability(f64::INFINITY).set_link_probability(f64::NAN);
+            let plain = generate_document_from_seed(Corpus::Embedded(Language::En), &nan, 3).unwrap();
+            assert!(!plain.to_markdown().contains("\n> ") && !plain.to_markdown().contains("]("));
+
+            let mut tricky = document.clone();
+            tricky.title = String::from("C# *and* [F#]");
+            tricky.introduction = vec![Block::Paragraph(vec![Inline::Text(String::from("- snake_case & <b>"))]), Block::Quote(String::from("1. a|b"))];
+            assert!(tricky.to_markdown().starts_with("# C\\# \\*and\\* \\[F\\#\\]\n\n\\- snake\\_case \\& \\<b\\>\n\n> 1\\. a\\|b\n"));
+        }
+
+        #[test]
+        // Test that paragraphs meet word, character, grapheme and byte targets
+        fn test_length_targets() {
+            use crate::text_generator::length::{Boundary, LengthTarget};
+            use unicode_segmentation::UnicodeSegmentation;
+
+            for seed in 0..10 {
+                let words = ParagraphOptions::new().set_length(LengthTarget::Words(37));
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::Ru), &words, seed).unwrap();
+                assert_eq!(text.split_whitespace().count(), 37);
+
+                let chars = ParagraphOptions::new().set_length(LengthTarget::Chars(100));
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &chars, seed).unwrap();
+                assert_eq!(text.chars().count(), 100);
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::Hi), &chars, seed).unwrap();
+                assert_eq!(text.chars().count(), 100);
+
+                let graphemes = ParagraphOptions::new().set_length(LengthTarget::Graphemes(50));
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::Hi), &graphemes, seed).unwrap();
+                assert_eq!(text.graphemes(true).count(), 50);
+
+                let bytes = ParagraphOptions::new().set_length(LengthTarget::Bytes(10));
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &bytes, seed).unwrap();
+                assert_eq!(text.len(), 10);
+
+                let sentences = chars.clone().set_boundary(Boundary::Sentence);
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &sentences, seed).unwrap();
+                assert!(text.ends_with('.') && text.chars().count() <= 100);
+
+                let ellipsis = chars.clone().set_boundary(Boundary::Word).set_ellipsis(true);
+                let text = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &ellipsis, seed).unwrap();
+                assert!(text.ends_with("…") && !text.ends_with(" …") && text.chars().count() <= 100);
+            }
+        }
+
+        #[test]
+        // Test that Zipf sampling favours high-ranked and high-frequency words
+        fn test_zipf_sampling() {
+            use crate::text_generator::error::GeneratorError;
+            use crate::text_generator::length::LengthTarget;
+            use crate::text_generator::sampling::Sampling;
+
+            let count = |text: &str, word: &str| text.split([' ', '.']).filter(|w| w.eq_ignore_ascii_case(word)).count();
+            let options = ParagraphOptions::new()
+                .set_length(LengthTarget::Words(5000))
+                .set_sampling(Sampling::Zipf { exponent: 1.0 });
+
+            let ranked: Vec<String> = (0..100).map(|i| format!("w{}", i)).collect();
+            let text = generate_paragraph_from_seed(Corpus::FromVec(ranked), &options, 5).unwrap();
+            assert!(count(&text, "w0") > 5 * count(&text, "w9"));
+            assert!(count(&text, "w99") > 0);
+
+            let counted = ["rare\t1", "common\t1000"];
+            let text = generate_paragraph_from_seed(Corpus::FromSlice(&counted), &options, 5).unwrap();
+            assert!(!text.contains('\t') && !text.contains("1000"));
+            assert!(count(&text, "common") > 100 * count(&text, "rare"));
+
+            let uniform = options.clone().set_sampling(Sampling::Uniform);
+            let text = generate_paragraph_from_seed(Corpus::FromSlice(&counted), &uniform, 5).unwrap();
+            assert!(count(&text, "rare") > 2000);
+
+            let nan = options.clone().set_sampling(Sampling::Zipf { exponent: f64::NAN });
+            assert!(matches!(generate_paragraph_from_seed(Corpus::FromSlice(&counted), &nan, 5), Err(GeneratorError::InvalidRange(_))));
+        }
+
+        #[test]
+        // Test that questions, exclamations, asides and quotations use the punctuation of each language
+        fn test_punctuation() {
+            use crate::text_generator::punctuation::Punctuation;
+
+            let questions = ParagraphOptions::new().set_punctuation(Punctuation::none().set_question(1.0));
+            let spanish = generate_paragraph_from_seed(Corpus::Embedded(Language::Es), &questions, 3).unwrap();
+            assert!(spanish.starts_with('¿') && spanish.ends_with('?'));
+            assert_eq!(spanish.matches('¿').count(), spanish.matches('?').count());
+
+            let french = generate_paragraph_from_seed(Corpus::Embedded(Language::Fr), &questions, 3).unwrap();
+            assert!(french.ends_with("\u{202f}?") && !french.contains('¿'));
+
+            let quotes = ParagraphOptions::new().set_punctuation(Punctuation::none().set_quotation(1.0));
+            let french = generate_paragraph_from_seed(Corpus::Embedded(Language::Fr), &quotes, 3).unwrap();
+            assert!(french.contains("«\u{a0}") && french.contains("\u{a0}»"));
+            let japanese = generate_paragraph_from_seed(Corpus::Embedded(Language::Ja), &quotes, 3).unwrap();
+            assert_eq!(japanese.matches('「').count(), japanese.matches('」').count());
+            assert!(japanese.contains('「') && !japanese.contains('“'));
+
+            let asides = ParagraphOptions::new().set_punctuation(Punctuation::none().set_parenthetical(1.0));
+            let english = generate_paragraph_from_seed(Corpus::FromSlice(&["one", "two"]), &asides, 3).unwrap();
+            assert_eq!(english.matches('(').count(), english.matches(')').count());
+            assert!(english.contains('(') && english.starts_with(char::is_uppercase));
+
+            // Without punctuation options every sentence is a plain statement
+            let plain = generate_paragraph_from_seed(Corpus::Embedded(Language::Es), &ParagraphOptions::new(), 3).unwrap();
+            assert!(!plain.contains(['¿', '?', ',', '(']));
+
+            // NaN probabilities count as 0 rather than panicking
+            let nan = Punctuation::none().set_comma(f64::NAN).set_semicolon(f64::NAN).set_parenthetical(f64::NAN).set_quotation(f64::NAN);
+            assert_eq!(nan, Punctuation::none());
+            let text = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &ParagraphOptions::new().set_punctuation(nan.set_question(f64::NAN)), 3).unwrap();
+            assert!(!text.contains(['?', ',', '(']));
+        }
+
+        #[test]
+        // Test that missing and empty corpora and out-of-range options are errors rather than panics
+        fn test_generator_errors() {
+            use crate::text_generator::error::GeneratorError;
+
+            let missing = try_generate_paragraph(Corpus::FromFile("corpus/missing.txt"), None, None);
+            assert!(matches!(missing, Err(GeneratorError::MissingCorpus(path)) if path == "corpus/missing.txt"));
+            let blank: Vec<String> = vec![String::new(), String::from("  "), String::from("\t")];
+            assert!(matches!(try_generate_paragraph(Corpus::FromVec(blank), None, None), Err(GeneratorError::EmptyCorpus)));
+            assert!(matches!(try_generate_paragraph(Corpus::FromSlice(&[]), None, None), Err(GeneratorError::EmptyCorpus)));
+            let huge = try_generate_paragraph(Corpus::FromSlice(&["word"]), Some(usize::MAX), None);
+            assert!(matches!(huge, Err(GeneratorError::InvalidRange(_))));
+            let huge = ParagraphOptions::new().set_min_sentences(usize::MAX - 1);
+            assert!(stream::paragraphs(Corpus::FromSlice(&["word"]), &huge, seeded_rng(1)).is_err());
+
+            // blank lines are skipped rather than producing empty words
+            let text = generate_paragraph_from_seed(Corpus::FromSlice(&["", "word", ""]), &ParagraphOptions::new(), 1).unwrap();
+            assert!(!text.contains("  ") && !text.contains(" ."));
+        }
+
+        #[test]
+        // Test that a loaded corpus is trimmed, deduplicated, indexed by length and shared across threads
+        fn test_loaded_vocabulary() {
+            let lines = vec![String::from(" alpha "), String::new(), String::from("beta"), String::from("alpha"), String::from("γάμμα")];
+            let vocabulary = Corpus::FromVec(lines).load().unwrap();
+            assert_eq!(vocabulary.words(), ["alpha", "beta", "γάμμα"]);
+            assert_eq!(vocabulary.with_length(5).collect::<Vec<&str>>(), ["alpha", "γάμμα"]);
+            assert_eq!(vocabulary.lengths().collect::<Vec<usize>>(), [4, 5]);
+
+            let counted = Corpus::FromSlice(&["a\t2", "b\t1", "a\t3"]).load().unwrap();
+            assert_eq!(counted.words(), ["a", "b"]);
+
+            let english = Corpus::Embedded(Language::En).load().unwrap();
+            assert_eq!(english.language(), Some(Language::En));
+            assert!(english.words().iter().all(|w| !w.is_empty() && w.trim() == w));
+
+            let handles: Vec<_> = (0..4).map(|seed| {
+                let vocabulary = vocabulary.clone();
+                std::thread::spawn(move || generate_paragraph_from_seed(Corpus::Loaded(vocabulary), &ParagraphOptions::new(), seed).unwrap())
+            }).collect();
+            for handle in handles {
+                let paragraph = handle.join().unwrap();
+                assert!(paragraph.split([' ', '.']).filter(|w| !w.is_empty()).all(|w| ["alpha", "beta", "γάμμα"].contains(&w.to_lowercase().as_str())));
+            }
+        }
+
+        #[test]
+        // Test that mixed-language text switches corpora and tags every span with its language
+        fn test_code_switching() {
+            use crate::text_generator::mixed::{generate_mixed_from_seed, Granularity, Mix, MixedOptions};
+
+            let mix = || Mix::new()
+                .add(Corpus::Embedded(Language::Hi), 1.0)
+                .add_tagged(Corpus::FromSlice(&["hello", "world"]), Language::En, 1.0);
+            let options = MixedOptions::new().set_granularity(Granularity::Word).set_min_sentences(5);
+            let mixed = generate_mixed_from_seed(mix(), &options, 11).unwrap();
+            assert_eq!(mixed, generate_mixed_from_seed(mix(), &options, 11).unwrap());
+
+            let mut languages = Vec::new();
+            for (text, language) in mixed.tagged() {
+                match language {
+                    Some(Language::En) => assert!(text.split(' ').all(|w| ["hello", "world"].contains(&w.to_lowercase().as_str()))),
+                    Some(Language::Hi) => assert!(!text.chars().any(|c| c.is_ascii_alphabetic())),
+                    other => panic!("unexpected tag {:?}", other),
+                }
+                languages.push(language);
+            }
+            assert!(languages.contains(&Some(Language::En)) && languages.contains(&Some(Language::Hi)));
+            assert!(mixed.spans.windows(2).all(|w| w[0].end < w[1].start));
+
+            let sentences = generate_mixed_from_seed(mix(), &options.clone().set_granularity(Granularity::Sentence), 11).unwrap();
+            assert!(sentences.spans.iter().all(|s| !sentences.text[s.start..s.end].contains(['.', '।'])));
+
+            assert!(generate_mixed_from_seed(Mix::new(), &options, 1).is_err());
+            assert!(generate_mixed_from_seed(Mix::new().add(Corpus::Embedded(Language::En), 0.0), &options, 1).is_err());
+        }
+
+        #[test]
+        // Test that templates fill their slots with words carrying the right part-of-speech tag
+        fn test_templates() {
+            use crate::text_generator::template::{generate_from_templates, Template};
+            use crate::text_generator::error::GeneratorError;
+
+            let vocabulary = Corpus::FromFile("corpus/tagged/english.txt").load().unwrap();
+            assert!(vocabulary.with_tag("verb:past").contains(&"walked"));
+            assert!(!vocabulary.with_tag("verb:past").contains(&"walks"));
+            assert!(vocabulary.with_tag("VERB").contains(&"walks"));
+
+            let template: Template = "The {adj} {noun} {verb:past} {adv}.".parse().unwrap();
+            let mut rng = seeded_rng(4);
+            for _ in 0..50 {
+                let sentence = template.fill(&vocabulary, &mut rng).unwrap();
+                let words: Vec<&str> = sentence.trim_end_matches('.').split(' ').collect();
+                assert_eq!(words[0], "The");
+                assert!(vocabulary.with_tag("adj").contains(&words[1]));
+                assert!(vocabulary.with_tag("noun").contains(&words[2]));
+                assert!(vocabulary.with_tag("verb:past").contains(&words[3]));
+                assert!(vocabulary.with_tag("adv").contains(&words[4]));
+            }
+
+            let templates = [template, Template::parse("{noun:plural} {verb:base} {{here}}.").unwrap()];
+            let paragraph = generate_from_templates(Corpus::Loaded(vocabulary.clone()), &templates, 4, &mut rng).unwrap();
+            assert!(paragraph.starts_with(char::is_uppercase) && paragraph.matches('.').count() == 4);
+
+            assert!(matches!(Template::parse("The {noun"), Err(GeneratorError::InvalidTemplate(_))));
+            assert!(matches!(Template::parse("a } b"), Err(GeneratorError::InvalidTemplate(_))));
+            let unknown = [Template::parse("{pronoun}").unwrap()];
+            assert!(matches!(generate_from_templates(Corpus::Loaded(vocabulary), &unknown, 1, &mut rng), Err(GeneratorError::UnknownTag(_))));
+        }
+
+        #[test]
+        // Test that the output writer honours its write mode and format and leaves no temporary files behind
+        fn test_output_writer() {
+            use crate::text_generator::output::{claim, Metadata, OutputFormat, OutputWriter, WriteMode};
+            use crate::text_generator::error::GeneratorError;
+
+            let dir = std::env::temp_dir().join(format!("text-manipulation-output-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            let path = dir.join("nested").join("out.txt");
+
+            let writer = OutputWriter::new();
+            assert_eq!(writer.write(&path, "first", &Metadata::new()).unwrap(), path);
+            assert!(writer.write(&path, "second", &Metadata::new()).is_err());
+
+            let unique = writer.clone().set_mode(WriteMode::UniqueSuffix);
+            assert_eq!(unique.write(&path, "second", &Metadata::new()).unwrap(), dir.join("nested").join("out-1.txt"));
+            assert_eq!(unique.write(&path, "third", &Metadata::new()).unwrap(), dir.join("nested").join("out-2.txt"));
+
+            writer.clone().set_mode(WriteMode::Append).write(&path, "more", &Metadata::new()).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "first\n\nmore");
+            writer.clone().set_mode(WriteMode::Overwrite).write(&path, "replaced", &Metadata::new()).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "replaced");
+
+            let metadata = Metadata::new().set_language(Language::Fr).set_seed(7).set_options(ParagraphOptions::default());
+            let lines = dir.join("out.ndjson");
+            let ndjson = writer.clone().set_format(OutputFormat::Ndjson).set_mode(WriteMode::Append);
+            ndjson.write(&lines, "un", &metadata).unwrap();
+            ndjson.write(&lines, "deux", &metadata).unwrap();
+            let records: Vec<Value> = fs::read_to_string(&lines).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[1]["text"], "deux");
+            assert_eq!(records[0]["language"], "fr");
+            assert_eq!(records[0]["seed"], 7);
+
+            let json = writer.clone().set_format(OutputFormat::Json);
+            json.write(dir.join("out.json"), "un", &metadata).unwrap();
+            let document: Value = serde_json::from_str(&fs::read_to_string(dir.join("out.json")).unwrap()).unwrap();
+            assert_eq!(document["text"], "un");
+            assert!(matches!(json.set_mode(WriteMode::Append).write(dir.join("out.json"), "deux", &metadata), Err(GeneratorError::UnsupportedOutput(_))));
+
+            // appends add to the file in place, so its permissions are kept, and NDJSON starts a new line if the file lacks one
+            fs::write(&lines, "{}").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&lines, fs::Permissions::from_mode(0o640)).unwrap();
+                ndjson.write(&lines, "trois", &metadata).unwrap();
+                assert_eq!(fs::metadata(&lines).unwrap().permissions().mode() & 0o777, 0o640);
+                assert_eq!(fs::read_to_string(&lines).unwrap().lines().count(), 2);
+            }
+
+            // without hard links, new files are claimed with create_new and copied into place
+            let unsupported = |_: &path::Path, _: &path::Path| Err(std::io::Error::from(std::io::ErrorKind::Unsupported));
+            let temp = dir.join("claim.tmp");
+            fs::write(&temp, "claimed").unwrap();
+            claim(&temp, &dir.join("claimed.txt"), unsupported).unwrap();
+            assert_eq!(fs::read_to_string(dir.join("claimed.txt")).unwrap(), "claimed");
+            assert_eq!(claim(&temp, &dir.join("claimed.txt"), unsupported).unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+            fs::remove_file(&temp).unwrap();
+
+            assert!(writer.set_create_dirs(false).write(dir.join("missing").join("out.txt"), "x", &Metadata::new()).is_err());
+            let leftovers = fs::read_dir(dir.join("nested")).unwrap().filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().ends_with(".tmp")).count();
+            assert_eq!(leftovers, 0);
+            fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[test]
+        // Test that bulk fixtures follow their layout and that the manifest matches the files on disk
+        fn test_fixtures() {
+            use crate::text_generator::fixtures::{generate_fixtures, FixtureLayout, ManifestFormat, SizeDistribution};
+
+            let root = std::env::temp_dir().join(format!("text-manipulation-fixtures-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+
+            let layout = FixtureLayout::new()
+                .set_documents(30)
+                .set_languages(&[Language::En, Language::Ja, Language::Ar])
+                .set_sizes(SizeDistribution::Weighted(vec![(100, 3.0), (2000, 1.0)]))
+                .set_depth(2)
+                .set_fanout(3);
+            let entries = generate_fixtures(&root, &layout, 5).unwrap();
+            assert_eq!(entries.len(), 30);
+
+            let csv = fs::read_to_string(root.join("manifest.csv")).unwrap();
+            assert_eq!(csv.lines().count(), 31);
+            for (entry, line) in entries.iter().zip(csv.lines().skip(1)) {
+                let text = fs::read_to_string(root.join(&entry.path)).unwrap();
+                assert_eq!(text.len(), entry.bytes);
+                assert!(entry.bytes > 90 && entry.bytes <= 2000 && entry.words > 0);
+                assert_eq!(entry.path.split('/').count(), 3);
+                assert!([Language::En, Language::Ja, Language::Ar].contains(&entry.language));
+                assert!(line.starts_with(&format!("{},{},{},", entry.path, entry.language.code(), entry.bytes)));
+
+                let size = if entry.bytes <= 100 { 100 } else { 2000 };
+                let options = ParagraphOptions::default().set_max_bytes(size);
+                assert_eq!(generate_paragraph_from_seed(Corpus::Embedded(entry.language), &options, entry.seed).unwrap(), text);
+            }
+
+            let again = generate_fixtures(&root, &layout.clone().set_manifest(ManifestFormat::Json), 5).unwrap();
+            assert_eq!(again, entries);
+            let manifest: Value = serde_json::from_str(&fs::read_to_string(root.join("manifest.json")).unwrap()).unwrap();
+            assert_eq!(manifest.as_array().unwrap().len(), 30);
+            assert_eq!(manifest[0]["path"], entries[0].path.as_str());
+
+            assert!(generate_fixtures(&root, &layout.clone().set_languages(&[]), 5).is_err());
+            assert!(generate_fixtures(&root, &layout.set_sizes(SizeDistribution::Uniform { min: 10, max: 5 }), 5).is_err());
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        #[test]
+        // Test that torture text reports every hazard it injects at the right place
+        fn test_torture_text() {
+            use crate::text_generator::torture::{generate_torture_from_seed, Hazard, TortureOptions};
+
+            let options = TortureOptions::new().set_rate(0.5).set_paragraph_options(ParagraphOptions::new().set_min_sentences(20));
+            let torture = generate_torture_from_seed(Corpus::Embedded(Language::Fr), &options, 9).unwrap();
+            assert_eq!(torture, generate_torture_from_seed(Corpus::Embedded(Language::Fr), &options, 9).unwrap());
+            assert_eq!(torture.counts().len(), Hazard::ALL.len());
+
+            for insertion in &torture.insertions {
+                let span = &torture.text[insertion.start..insertion.end];
+                match insertion.hazard {
+                    Hazard::CombiningStack => assert!(span.chars().any(|c| ('\u{300}'..='\u{36F}').contains(&c))),
+                    Hazard::ZeroWidth => assert!(span.contains(['\u{200D}', '\u{200C}', '\u{200B}', '\u{2060}'])),
+                    Hazard::BidiControl => assert!(span.contains(['\u{202C}', '\u{2069}']) && span.chars().any(|c| ('\u{600}'..='\u{6FF}').contains(&c))),
+                    Hazard::EmojiSequence => assert!(span.contains('\u{200D}') || span.contains('\u{E007F}')),
+                    Hazard::AstralPlane => assert!(span.chars().any(|c| c as u32 > 0xFFFF)),
+                    Hazard::Normalization => assert!(span.chars().any(|c| ('\u{300}'..='\u{36F}').contains(&c))),
+                    Hazard::LoneSurrogate => assert!(span.contains('\u{FFFD}')),
+                }
+            }
+            assert!(torture.insertions.windows(2).all(|w| w[0].end <= w[1].start));
+
+            let json = torture.to_json_string();
+            assert!(json.starts_with('"') && json.contains("\\ud") && !json.contains('\u{FFFD}'));
+            assert!(serde_json::from_str::<String>(&json).is_err());
+
+            let arabic = generate_torture_from_seed(Corpus::Embedded(Language::Ar), &options.clone().set_hazards(&[Hazard::BidiControl]), 2).unwrap();
+            assert!(arabic.insertions.iter().all(|i| arabic.text[i.start..i.end].chars().any(|c| c.is_ascii_alphabetic())));
+
+            let clean = generate_torture_from_seed(Corpus::Embedded(Language::En), &options.clone().set_rate(0.0), 9).unwrap();
+            assert!(clean.insertions.is_empty());
+            assert_eq!(serde_json::from_str::<String>(&clean.to_json_string()).unwrap(), clean.text);
+
+            // a NaN rate must not reach gen_bool, which panics on it
+            let nan = generate_torture_from_seed(Corpus::Embedded(Language::En), &options.set_rate(f64::NAN), 9).unwrap();
+            assert_eq!(nan.text, clean.text);
+        }
+
+        #[test]
+        // Test that corpus statistics catch blank, padded, duplicate and mixed-script lines
+        fn test_corpus_stats() {
+            use crate::text_generator::stats::{corpus_stats, Script};
+
+            let words = ["cat", " dog", "cat", "", "naïve", "кот", "Cafe\u{301}", "pаris", "ひらがなカタカナ漢字", "dog\r", ""];
+            let stats = corpus_stats(Corpus::FromSlice(&words)).unwrap();
+            assert_eq!(stats.lines, 10);
+            assert_eq!(stats.blank_lines, 1);
+            assert_eq!(stats.padded_lines, 2);
+            assert_eq!(stats.duplicate_lines, 2);
+            assert_eq!(stats.vocabulary_size, 7);
+            assert_eq!(stats.char_lengths[&5], 3);
+            assert_eq!(stats.grapheme_lengths[&4], 1);
+            assert_eq!(stats.scripts[&Script::Cyrillic], 2);
+            assert_eq!(stats.mixed_script_words, vec!["pаris"]);
+            assert!(stats.characters.contains(&'ï') && !stats.characters.contains(&' '));
+            assert!(!stats.is_clean());
+
+            let english = corpus_stats(Corpus::Embedded(Language::En)).unwrap();
+            assert_eq!(english.vocabulary_size, Corpus::Embedded(Language::En).load().unwrap().len());
+            assert_eq!(english.scripts.keys().collect::<Vec<_>>(), vec![&Script::Latin]);
+            let clean = corpus_stats(Corpus::FromVec(vec![String::from("one"), String::from("two"), String::new()])).unwrap();
+            assert!(clean.is_clean() && clean.lines == 2);
+            assert!(corpus_stats(Corpus::FromFile("corpus/missing.txt")).is_err());
+        }
+
+        #[test]
+        // Test that the corpus builder strips markup, filters tokens and writes a ranked word list the generator can read
+        fn test_corpus_builder() {
+            use crate::text_generator::builder::{CorpusBuilder, SourceFormat};
+
+            let mut builder = CorpusBuilder::new().set_stopwords(&["A"]);
+            builder.add_document("The cat saw a dog. The dog ran in 2023, see https://example.com or mail me@example.org.", SourceFormat::Text);
+            builder.add_document("# The Cat\n\nA [cat](http://cats.example) `let dog = 1;` sat.\n\n```\nfn main() {}\n```\n", SourceFormat::Markdown);
+            builder.add_document("<html><head><style>p { color: red }</style><script>var cat;</script></head><body><p>Caf&eacute; caf&#233; &amp; the <b>cat</b><!-- dog --></p></body></html>", SourceFormat::Html);
+            builder.add_document("猫が好きです。ネコ", SourceFormat::Text);
+
+            let ranked = builder.ranked();
+            assert_eq!(ranked[0], (String::from("cat"), 4));
+            assert_eq!(ranked[1], (String::from("the"), 4));
+            assert_eq!(ranked[2], (String::from("café"), 2));
+            assert_eq!(ranked[3], (String::from("dog"), 2));
+            let words: Vec<&str> = ranked.iter().map(|(w, _)| w.as_str()).collect();
+            for absent in ["a", "2023", "https", "example.com", "let", "fn", "main", "color", "var", "http"] {
+                assert!(!words.contains(&absent), "{} should be filtered", absent);
+            }
+            assert!(words.contains(&"猫が") && words.contains(&"好きです") && words.contains(&"ネコ"));
+
+            let urls = builder.clone().set_keep_urls(true).set_keep_numbers(true).set_max_words(3);
+            assert_eq!(urls.ranked().len(), 3);
+            let mut kept = CorpusBuilder::new().set_keep_urls(true).set_keep_numbers(true).set_lowercase(false);
+            kept.add_document("Visit <https://example.com>, Haus 42 Haus", SourceFormat::Text);
+            assert_eq!(kept.ranked(), vec![(String::from("Haus"), 2), (String::from("42"), 1), (String::from("Visit"), 1), (String::from("https://example.com"), 1)]);
+
+            let path = std::env::temp_dir().join(format!("text-manipulation-built-{}.txt", std::process::id()));
+            builder.clone().set_min_count(2).write(&path).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "cat\t4\nthe\t4\ncafé\t2\ndog\t2\n");
+            let vocabulary = Corpus::FromFile(path.to_str().unwrap()).load().unwrap();
+            assert_eq!(vocabulary.words(), ["cat", "the", "café", "dog"]);
+            fs::remove_file(&path).unwrap();
+
+            assert!(matches!(CorpusBuilder::new().write(&path), Err(crate::text_generator::error::GeneratorError::EmptyCorpus)));
+            assert_eq!(builder.build().unwrap().len(), ranked.len());
+        }
+
+        #[test]
+        // Test that pseudo-localization accents and pads text but leaves ICU and printf placeholders alone
+        fn test_pseudo_localization() {
+            use crate::pseudo_locale::{pseudo_localize, PseudoLocale};
+
+            assert_eq!(pseudo_localize("Settings"), "[Ŝéţţîñĝš ~~~]");
+            assert_eq!(pseudo_localize("Hello, {name}!"), "[Ĥéļļö, {name}! ~~~]");
+            assert_eq!(pseudo_localize("%1$s of %2$d files (%.1f%%)"), "[%1$s öƒ %2$d ƒîļéš (%.1f%%) ~~~~]");
+            assert_eq!(pseudo_localize("Hi %(user)s, 50% off"), "[Ĥî %(user)s, 50% öƒƒ ~~~~]");
+            assert_eq!(pseudo_localize("Welcome, %{name}"), "[Ŵéļçöɱé, %{name} ~~~]");
+            assert_eq!(
+                pseudo_localize("{count, plural, one {# file} other {# files in {folder}}} on {d, date, short}"),
+                "[{count, plural, one {# ƒîļé} other {# ƒîļéš îñ {folder}}} öñ {d, date, short} ~~~~~~]"
+            );
+            assert_eq!(
+                pseudo_localize("{gender, select, female {She has # #tags} other {They}}"),
+                "[{gender, select, female {Ŝĥé ĥáš # #ţáĝš} other {Ţĥéý}} ~~~~~~]"
+            );
+            assert_eq!(pseudo_localize("Press {Ctrl + S} to save"), "[Þŕéšš {Çţŕļ + Ŝ} ţö šáṽé ~~~~~~~~]");
+
+            let plain = PseudoLocale::new().set_accents(false).set_brackets(false).set_expansion(100);
+            assert_eq!(plain.transform("Save {file}"), "Save {file} ~~~~~");
+            assert_eq!(plain.clone().set_expansion(0).transform("Save"), "Save");
+
+            let mirrored = PseudoLocale::new().set_mirror(true).set_expansion(0).transform("Open {file} now");
+            assert_eq!(mirrored, "[\u{202E}Öþéñ \u{202C}{file}\u{202E} ñöŵ\u{202C}]");
+        }
+
+        #[test]
+        // Test that resource files keep their keys and placeholders while every value is filled with text of the same length
+        fn test_resource_generation() {
+            use crate::text_generator::resources::{generate_resources, ResourceFile, ResourceFormat, ResourceOptions};
+            use unicode_segmentation::UnicodeSegmentation;
+
+            let options = ResourceOptions::new().set_languages(&[Language::Fr, Language::Ja]);
+            let json = ResourceFile::parse(r#"{"menu": {"save": "Save", "greeting": "Hello, {name}!"}, "count": "%d files"}"#, ResourceFormat::Json).unwrap();
+            let french_text = json.localize(Language::Fr, &options, &mut seeded_rng(1)).unwrap();
+            let french: Value = serde_json::from_str(&french_text).unwrap();
+            assert_eq!(french["menu"]["save"].as_str().unwrap().graphemes(true).count(), 4);
+            let greeting = french["menu"]["greeting"].as_str().unwrap();
+            assert!(greeting.ends_with(", {name}!") && greeting != "Hello, {name}!");
+            assert!(french["count"].as_str().unwrap().starts_with("%d "));
+            // keys keep their source order, which serde_json::Value would sort
+            let position = |text: &str, key: &str| text.find(&format!("\"{}\":", key)).unwrap();
+            assert!(position(&french_text, "menu") < position(&french_text, "count"));
+            assert!(position(&french_text, "save") < position(&french_text, "greeting"));
+            let unsorted = ResourceFile::parse(r#"{"zoom": "Zoom", "about": "About", "help": "Help", "exit": 1.5}"#, ResourceFormat::Json).unwrap();
+            let unsorted = unsorted.localize(Language::Fr, &options, &mut seeded_rng(1)).unwrap();
+            let positions: Vec<usize> = ["zoom", "about", "help", "exit"].iter().map(|key| position(&unsorted, key)).collect();
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(unsorted.contains("\"exit\": 1.5"));
+
+            let yaml = ResourceFile::parse("en:\n  title: Welcome back %{user}\n  items: [One, Two]\n", ResourceFormat::Yaml).unwrap();
+            let japanese: serde_yaml::Value = serde_yaml::from_str(&yaml.localize(Language::Ja, &options, &mut seeded_rng(1)).unwrap()).unwrap();
+            let title = japanese["ja"]["title"].as_str().unwrap().strip_suffix(" %{user}").unwrap();
+            assert_eq!(title.graphemes(true).count(), 12);
+            assert_eq!(japanese["ja"]["items"].as_sequence().unwrap().len(), 2);
+
+            let po = "msgid \"\"\nmsgstr \"\"\n\"Language: en\\n\"\n\n#: src/main.rs:3\nmsgid \"Open file\"\nmsgstr \"\"\n\nmsgid \"%d item\"\nmsgid_plural \"%d items\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+            let po = ResourceFile::parse(po, ResourceFormat::Po).unwrap().localize(Language::Fr, &options, &mut seeded_rng(1)).unwrap();
+            let lines: Vec<&str> = po.lines().collect();
+            assert_eq!(lines[1], "msgstr \"Language: fr\\n\"");
+            assert_eq!(&lines[3..5], ["#: src/main.rs:3", "msgid \"Open file\""]);
+            assert!(lines[5].starts_with("msgstr \"") && lines[5] != "msgstr \"Open file\"");
+            assert!(lines[9].starts_with("msgstr[0] \"%d ") && lines[10].starts_with("msgstr[1] \"%d "));
+
+            let ftl = "# Greetings\nhello = Hello, { $name }!\nemails =\n    { $n ->\n        [one] One new email\n       *[other] { $n } new emails\n    }\nlogin = Log in\n    .title = Sign in to continue\n";
+            let ftl = ResourceFile::parse(ftl, ResourceFormat::Fluent).unwrap().localize(Language::Fr, &options, &mut seeded_rng(1)).unwrap();
+            let lines: Vec<&str> = ftl.lines().collect();
+            assert_eq!(lines.len(), 9);
+            assert_eq!(&lines[..4], ["# Greetings", lines[1], "emails =", "    { $n ->"]);
+            assert!(lines[1].starts_with("hello = ") && lines[1].ends_with(", { $name }!"));
+            assert!(lines[4].starts_with("        [one] ") && lines[5].starts_with("       *[other] { $n } "));
+            assert!(lines[8].starts_with("    .title = "));
+
+            assert!(ResourceFile::parse("hello world", ResourceFormat::Fluent).is_err());
+            assert!(ResourceFile::parse("msgid \"a\"\nmsgstr unquoted\n", ResourceFormat::Po).is_err());
+
+            let dir = std::env::temp_dir().join(format!("text-manipulation-resources-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("en.json"), r#"{"save": "Save"}"#).unwrap();
+            let paths = generate_resources(dir.join("en.json"), dir.join("out"), &options, 7).unwrap();
+            assert_eq!(paths, [dir.join("out/fr.json"), dir.join("out/ja.json")]);
+            let again = generate_resources(dir.join("en.json"), dir.join("out"), &options.set_languages(&[Language::Ja]), 7).unwrap();
+            assert_eq!(fs::read_to_string(&again[0]).unwrap(), fs::read_to_string(&paths[1]).unwrap());
+            fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[test]
+        // Test that a parallel corpus aligns each source sentence with its translation in every output format
+        fn test_parallel_corpus() {
+            use crate::parallel::{build_parallel_corpus, ParallelFormat, ParallelOptions, Provider};
+            use crate::pseudo_locale::pseudo_localize;
+
+            let options = ParallelOptions::new().set_sentences(5).set_source(Language::Fr).set_target("en-XA");
+            let corpus = build_parallel_corpus(&[Provider::Pseudo], &options, 9).unwrap();
+            assert_eq!(corpus.pairs.len(), 5);
+            for (i, pair) in corpus.pairs.iter().enumerate() {
+                assert_eq!(pair.id, i + 1);
+                assert_eq!(pair.target, pseudo_localize(&pair.source));
+                assert_eq!((pair.provider, pair.glossary.as_deref()), ("pseudo", None));
+            }
+            let sentences: Vec<String> = stream::sentences(Corpus::Embedded(Language::Fr), &ParagraphOptions::new(), seeded_rng(9)).unwrap().take(5).collect();
+            assert_eq!(corpus.pairs.iter().map(|p| &p.source).collect::<Vec<&String>>(), sentences.iter().collect::<Vec<&String>>());
+
+            let tsv = corpus.render(ParallelFormat::Tsv);
+            assert_eq!(tsv.lines().count(), 6);
+            assert_eq!(tsv.lines().nth(1).unwrap(), format!("1\t{}\t{}\tpseudo\t", corpus.pairs[0].source, corpus.pairs[0].target));
+
+            let jsonl = corpus.render(ParallelFormat::Jsonl);
+            let first: Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+            assert_eq!(first["source_language"], "fr");
+            assert_eq!(first["target"], corpus.pairs[0].target.as_str());
+            assert!(first["glossary"].is_null());
+
+            let mut tricky = corpus.clone();
+            tricky.pairs[0].source = String::from("Fish & <chips>");
+            let tmx = tricky.render(ParallelFormat::Tmx);
+            assert_eq!(tmx.matches("<tu tuid=").count(), 5);
+            assert!(tmx.contains("<tuv xml:lang=\"fr\"><seg>Fish &amp; &lt;chips&gt;</seg></tuv>"));
+            assert!(tmx.contains("<prop type=\"x-provider\">pseudo</prop>"));
+
+            assert_eq!(ParallelFormat::from_path("pairs.jsonl"), Some(ParallelFormat::Jsonl));
+            assert!(build_parallel_corpus(&[], &options, 9).is_err());
+            assert!(build_parallel_corpus(&[Provider::DeepL { key: DeepLKey { key: String::new() }, glossary: None }], &options.set_source(Language::Hi), 9).is_err());
+        }
+
+        #[test]
+        // Test readability scores on known text and that generated paragraphs land in the requested band
+        fn test_readability() {
+            use crate::text_generator::readability::{syllables, Readability, ReadingLevel};
+            use crate::text_generator::sampling::Sampling;
+
+            let counts: Vec<usize> = ["the", "cake", "table", "readability", "ice", "日本語", "नमस्ते"].iter().map(|w| syllables(w)).collect();
+            assert_eq!(counts, [1, 1, 2, 5, 1, 3, 3]);
+            let cat = Readability::of("The cat sat on the mat. It was happy!");
+            assert_eq!(cat, Readability { sentences: 2, words: 9, syllables: 10, long_words: 0 });
+            assert!((Readability::of("The cat sat on the mat.").flesch_reading_ease() - 116.145).abs() < 1e-9);
+            assert!((Readability::of("The cat sat on the mat.").flesch_kincaid_grade() + 1.45).abs() < 1e-9);
+            assert!((Readability::of("Kommunikationen fungerar bra. Det är fint.").lix() - (3.0 + 100.0 / 3.0)).abs() < 1e-9);
+            assert_eq!(Readability::of("").flesch_kincaid_grade(), -15.59);
+
+            let levels = [
+                ReadingLevel::FleschReadingEase { min: 60.0, max: 70.0 },
+                ReadingLevel::FleschKincaidGrade { min: 10.0, max: 12.0 },
+                ReadingLevel::Lix { min: 40.0, max: 50.0 },
+            ];
+            for level in levels {
+                let options = ParagraphOptions::new().set_min_sentences(30).set_reading_level(level).set_sampling(Sampling::Zipf { exponent: 1.0 });
+                let paragraph = generate_paragraph_from_seed(Corpus::Embedded(Language::En), &options, 3).unwrap();
+                assert!(level.contains(&Readability::of(&paragraph)), "{:?} scored {}", level, level.score(&Readability::of(&paragraph)));
+            }
+            let swedish = ParagraphOptions::new().set_min_sentences(30).set_reading_level(ReadingLevel::Lix { min: 30.0, max: 40.0 });
+            let paragraph = generate_paragraph_from_seed(Corpus::Embedded(Language::Sv), &swedish, 3).unwrap();
+            assert!((30.0..=40.0).contains(&Readability::of(&paragraph).lix()));
+
+            let inverted = ParagraphOptions::new().set_reading_level(ReadingLevel::Lix { min: 50.0, max: 40.0 });
+            assert!(generate_paragraph_from_seed(Corpus::Embedded(Language::En), &inverted, 3).is_err());
+        }
+
+        #[test]
+        // Test locale-aware casing and that generated sentences are cased with it
+        fn test_casing() {
+            use crate::casing::{capitalize_first, to_lower, to_sentence, to_title, to_upper, CaseLocale};
+            use crate::text_generator::typography::Typography;
+
+            assert_eq!(to_upper("straße", CaseLocale::Root), "STRASSE");
+            assert_eq!(to_lower("ΟΔΟΣ ÉCOLE", CaseLocale::Root), "οδος école");
+            assert_eq!(to_upper("istanbul ılık", CaseLocale::Turkic), "İSTANBUL ILIK");
+            assert_eq!(to_lower("İSTANBUL ILIK", CaseLocale::Turkic), "istanbul ılık");
+            assert_eq!(to_lower("İ", CaseLocale::Root), "i\u{307}");
+            assert_eq!(to_upper("Αθήνα ᾠδή", CaseLocale::Greek), "ΑΘΗΝΑ ΩΙΔΗ");
+            assert_eq!(to_upper("Αθήνα", CaseLocale::Root), "ΑΘΉΝΑ");
+            assert_eq!(to_title("ijsselmeer and ǆungla", CaseLocale::Dutch), "IJsselmeer And ǅungla");
+            assert_eq!(to_title("ijsselmeer ﬁne", CaseLocale::Root), "Ijsselmeer Fine");
+            assert_eq!(to_title("iğdır", CaseLocale::Turkic), "İğdır");
+            assert_eq!(to_sentence("«ÉCOLE Primaire»", CaseLocale::Root), "«École primaire»");
+            assert_eq!(capitalize_first("12 élèves", CaseLocale::Root), "12 élèves");
+            assert_eq!(CaseLocale::from_tag("tr-TR"), CaseLocale::Turkic);
+            assert_eq!(Language::De.typography().case_locale, CaseLocale::Root);
+
+            let options = ParagraphOptions::new().set_min_sentences(5).set_typography(Language::Ru.typography());
+            let paragraph = generate_paragraph_from_seed(Corpus::FromSlice(&["МОСКВА", "ÉCOLE", "Ärger"]), &options, 4).unwrap();
+            for sentence in paragraph.split(". ") {
+                assert!(sentence.chars().next().unwrap().is_uppercase());
+                assert!(sentence.chars().skip(1).all(|c| !c.is_uppercase()), "{}", sentence);
+            }
+            let turkish = ParagraphOptions::new().set_min_sentences(2).set_typography(Typography { case_locale: CaseLocale::Turkic, ..Typography::latin() });
+            let paragraph = generate_paragraph_from_seed(Corpus::FromSlice(&["IRMAK", "İNCİ"]), &turkish, 4).unwrap();
+            assert!(paragraph.contains("ırmak") || paragraph.contains("inci"));
+            assert!(!paragraph.contains('\u{307}') && !paragraph.contains("irmak"));
+        }
+
+        #[test]
+        // Test that in-memory, reader and directory corpora only produce their own words
+        fn test_corpus_sources() {
+            let vocabulary = ["alpha", "beta", "gamma"];
+            let only_vocabulary = |paragraph: String| {
+                paragraph.split([' ', '.'])
+                    .filter(|w| !w.is_empty())
+                    .all(|w| vocabulary.contains(&w.to_lowercase().as_str()))
+            };
+
+            let words: Vec<String> = vocabulary.iter().map(|w| w.to_string()).collect();
+            assert!(only_vocabulary(generate_paragraph(Corpus::FromVec(words), None, None)));
+            assert!(only_vocabulary(generate_paragraph(Corpus::FromSlice(&vocabulary), None, None)));
+
+            let reader = std::io::Cursor::new("alpha\nbeta\ngamma");
+            assert!(only_vocabulary(generate_paragraph(Corpus::FromReader(Box::new(reader)), None, None)));
+
+            fs::create_dir_all("test_corpus_dir").unwrap();
+            fs::write("test_corpus_dir/a.txt", "alpha\nbeta").unwrap();
+            fs::write("test_corpus_dir/b.txt", "gamma").unwrap();
+            let paragraph = generate_paragraph(Corpus::FromDir("test_corpus_dir"), None, None);
+            fs::remove_dir_all("test_corpus_dir").unwrap();
+            assert!(only_vocabulary(paragraph));
+        }
+
         #[test]
         fn test_write_paragraph_to_file() {
             let paragraph = String::from("Hello world!");
@@ -57,6 +840,17 @@ pub mod text_manipulation{
             fs::remove_file("test.txt").unwrap();
         }
 
+        #[test]
+        // Test that MyMemory queries are percent-encoded, so questions and ampersands are not cut off
+        fn test_my_memory_query_encoding() {
+            use crate::my_memory::TranslationRequest;
+
+            let url = TranslationRequest::new(String::from("Fish & chips? Ça va"), String::from("en|fr")).url();
+            assert_eq!(url.query(), Some("q=Fish+%26+chips%3F+%C3%87a+va&langpair=en%7Cfr"));
+            let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+            assert_eq!(pairs, [(String::from("q"), String::from("Fish & chips? Ça va")), (String::from("langpair"), String::from("en|fr"))]);
+        }
+
         #[test]
         fn mymemory_nomral_operation() {
             // use crate::text_manipulation::{translate_q_langpair};
@@ -2,37 +2,47 @@
 use reqwest;
 use reqwest::blocking::Client;
 
+/// The MyMemory translation endpoint.
+const ENDPOINT: &str = "https://api.mymemory.translated.net/get";
+
 /// This struct holds the parameters for the MyMemory API request.
-struct TranslationRequest {
+pub(crate) struct TranslationRequest {
     q: String,
     langpair: String,
 }
 /// This impl block contains the constructor for the TranslationRequest struct.
 impl TranslationRequest {
-    fn new(q: String, langpair: String) -> Self {
+    pub(crate) fn new(q: String, langpair: String) -> Self {
         Self { q, langpair }
     }
+
+    /// The request URL.  `q` and `langpair` are percent-encoded, so text with spaces, `?`, `&` or non-ASCII characters arrives intact.
+    pub(crate) fn url(&self) -> reqwest::Url {
+        reqwest::Url::parse_with_params(ENDPOINT, &[("q", &self.q), ("langpair", &self.langpair)])
+            .expect("the MyMemory endpoint is a valid URL")
+    }
 }
 
 /// This function makes the request to the MyMemory API.
 fn translate(request: TranslationRequest) -> Result<String, reqwest::Error> {
-    let url = format!(
-        "https://api.mymemory.translated.net/get?q={}&langpair={}",
-        request.q, request.langpair
-    );
     let client = Client::new();
-    let response = client.get(&url).send()?;
+    let response = client.get(request.url()).send()?;
     let response_text = response.text()?;
     Ok(response_text)
 }
-/// This function takes the request parameters and returns the translated text.
+/// This function takes the request parameters and returns the translated text.  It panics if the request fails; use [try_translate_q_langpair] to handle errors instead.
 pub fn translate_q_langpair(q: String, langpair: String) -> String { 
+    try_translate_q_langpair(q, langpair).unwrap()
+}
+
+/// This function takes the request parameters and returns the translated text, or an error if the request fails or the response cannot be read.
+pub fn try_translate_q_langpair(q: String, langpair: String) -> Result<String, Box<dyn std::error::Error>> {
     let translation_request = TranslationRequest::new(q, langpair);
-    let response_text = translate(translation_request).unwrap();
-    let response_json: serde_json::Value = serde_json::from_str(&response_text).unwrap();
+    let response_text = translate(translation_request)?;
+    let response_json: serde_json::Value = serde_json::from_str(&response_text)?;
     let translated_text = response_json["responseData"]["translatedText"]
         .as_str()
         .unwrap_or_default()
         .to_owned();
-    translated_text
+    Ok(translated_text)
 }
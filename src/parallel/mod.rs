@@ -0,0 +1,300 @@
+//! Synthetic parallel corpora for testing alignment and bilingual search.  Source sentences are generated with `text_generator`, each one is translated through DeepL or MyMemory, and the aligned pairs are written as TSV, JSON Lines or TMX.  Every pair records the provider that translated it and the glossary used, if any.
+
+use serde::Serialize;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::deepl::{DeepLKey, SourceLang, TargetLang};
+use crate::my_memory::try_translate_q_langpair;
+use crate::pseudo_locale::PseudoLocale;
+use crate::request::http_request::HttpResponseType;
+use crate::request::translation_request::TranslationRequest;
+use crate::text_generator::error::GeneratorError;
+use crate::text_generator::language::Language;
+use crate::text_generator::output::{Metadata, OutputWriter, WriteMode};
+use crate::text_generator::{seeded_rng, stream, Corpus, ParagraphOptions};
+
+/// A translation service.
+pub enum Provider {
+    /// DeepL, with an API key and optionally the ID of a glossary to translate with
+    DeepL { key: DeepLKey, glossary: Option<String> },
+
+    /// MyMemory, which needs no key
+    MyMemory,
+
+    /// Pseudo-localization, which runs offline.  Useful to check a pipeline before spending API quota.
+    Pseudo,
+}
+
+impl Provider {
+    /// The name recorded for the pairs this provider translates.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::DeepL { .. } => "deepl",
+            Provider::MyMemory => "mymemory",
+            Provider::Pseudo => "pseudo",
+        }
+    }
+
+    /// The ID of the glossary this provider translates with, if any.
+    pub fn glossary(&self) -> Option<&str> {
+        match self {
+            Provider::DeepL { glossary, .. } => glossary.as_deref(),
+            _ => None
+        }
+    }
+
+    /// Translate one sentence from a bundled language into a language code such as "de" or "pt-BR".
+    pub fn translate(&self, text: &str, from: Language, to: &str) -> Result<String, Box<dyn Error>> {
+        match self {
+            Provider::DeepL { key, glossary } => {
+                let source = SourceLang::from_str(&from.code().to_uppercase())
+                    .map_err(|_| format!("DeepL does not translate from {}", from.name()))?;
+                let target = TargetLang::from_str(&to.to_uppercase())
+                    .map_err(|_| format!("DeepL does not translate into {}", to))?;
+
+                // a glossary needs the source language, so it is always given
+                let mut request = TranslationRequest::new(text, target).set_source_lang(source);
+                if let Some(id) = glossary {
+                    request = request.set_glossary_id(id);
+                }
+                let translated = match request.create_request(key).execute()? {
+                    HttpResponseType::Json(j) => j["translations"][0]["text"].as_str().map(|t| t.to_owned()),
+                    HttpResponseType::Tsv(_) => None
+                };
+                Ok(translated.ok_or("DeepL returned no translation")?)
+            },
+            Provider::MyMemory => {
+                let translated = try_translate_q_langpair(text.to_owned(), format!("{}|{}", from.code(), to))?;
+                if translated.is_empty() {
+                    return Err("MyMemory returned no translation".into());
+                }
+                Ok(translated)
+            },
+            Provider::Pseudo => Ok(PseudoLocale::new().transform(text)),
+        }
+    }
+}
+
+/// Options for a parallel corpus.
+#[derive(Clone, Debug)]
+pub struct ParallelOptions {
+    /// Number of sentence pairs
+    sentences: usize,
+
+    /// The language source sentences are generated in
+    source: Language,
+
+    /// The code of the language sentences are translated into
+    target: String,
+
+    /// Options for the generated sentences
+    paragraph: ParagraphOptions,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        ParallelOptions { sentences: 100, source: Language::En, target: String::from("de"), paragraph: ParagraphOptions::default() }
+    }
+}
+
+impl ParallelOptions {
+    /// 100 English sentences translated into German.
+    pub fn new() -> ParallelOptions {
+        ParallelOptions::default()
+    }
+
+    /// Set the number of sentence pairs.
+    pub fn set_sentences(mut self, n: usize) -> ParallelOptions {
+        self.sentences = n;
+        self
+    }
+
+    /// Set the language source sentences are generated in.
+    pub fn set_source(mut self, language: Language) -> ParallelOptions {
+        self.source = language;
+        self
+    }
+
+    /// Set the code of the language to translate into, e.g. "fr" or "en-GB".
+    pub fn set_target(mut self, code: &str) -> ParallelOptions {
+        self.target = code.to_owned();
+        self
+    }
+
+    /// Set the options for the generated sentences, e.g. their punctuation.
+    pub fn set_paragraph_options(mut self, options: ParagraphOptions) -> ParallelOptions {
+        self.paragraph = options;
+        self
+    }
+}
+
+/// A source sentence and its translation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SentencePair {
+    /// Position of the pair in the corpus, starting at 1
+    pub id: usize,
+    pub source: String,
+    pub target: String,
+
+    /// The name of the provider that translated the sentence
+    pub provider: &'static str,
+
+    /// The ID of the glossary used, if any
+    pub glossary: Option<String>,
+}
+
+/// The ways a parallel corpus can be written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParallelFormat {
+    /// Tab-separated values with a header row: id, source, target, provider and glossary.  Tabs and line breaks inside sentences become spaces.
+    #[default]
+    Tsv,
+
+    /// One JSON object per line
+    Jsonl,
+
+    /// Translation Memory eXchange 1.4, with the provider and glossary as properties of each unit
+    Tmx,
+}
+
+impl ParallelFormat {
+    /// The format of a file, going by its extension, or None if the extension is not recognized.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<ParallelFormat> {
+        let extension = path.as_ref().extension().map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("tsv") | Some("tab") => Some(ParallelFormat::Tsv),
+            Some("jsonl") | Some("ndjson") => Some(ParallelFormat::Jsonl),
+            Some("tmx") => Some(ParallelFormat::Tmx),
+            _ => None
+        }
+    }
+}
+
+/// Aligned sentence pairs between two languages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParallelCorpus {
+    pub source_language: Language,
+
+    /// The code of the target language, as it was given
+    pub target_language: String,
+
+    /// The seed the source sentences were generated from
+    pub seed: u64,
+    pub pairs: Vec<SentencePair>,
+}
+
+impl ParallelCorpus {
+    /// The corpus in a format.
+    pub fn render(&self, format: ParallelFormat) -> String {
+        match format {
+            ParallelFormat::Tsv => self.to_tsv(),
+            ParallelFormat::Jsonl => self.to_jsonl(),
+            ParallelFormat::Tmx => self.to_tmx(),
+        }
+    }
+
+    fn to_tsv(&self) -> String {
+        let mut tsv = String::from("id\tsource\ttarget\tprovider\tglossary\n");
+        for pair in &self.pairs {
+            tsv.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                pair.id, tsv_field(&pair.source), tsv_field(&pair.target), pair.provider, tsv_field(pair.glossary.as_deref().unwrap_or_default())
+            ));
+        }
+        tsv
+    }
+
+    fn to_jsonl(&self) -> String {
+        let mut jsonl = String::new();
+        for pair in &self.pairs {
+            let line = serde_json::json!({
+                "id": pair.id,
+                "source_language": self.source_language.code(),
+                "target_language": self.target_language,
+                "source": pair.source,
+                "target": pair.target,
+                "provider": pair.provider,
+                "glossary": pair.glossary,
+            });
+            jsonl.push_str(&line.to_string());
+            jsonl.push('\n');
+        }
+        jsonl
+    }
+
+    fn to_tmx(&self) -> String {
+        let source = self.source_language.code();
+        let mut tmx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+        tmx.push_str(&format!(
+            "  <header creationtool=\"{}\" creationtoolversion=\"{}\" segtype=\"sentence\" o-tmf=\"plaintext\" adminlang=\"en\" srclang=\"{}\" datatype=\"plaintext\"/>\n  <body>\n",
+            env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), source
+        ));
+        for pair in &self.pairs {
+            tmx.push_str(&format!("    <tu tuid=\"{}\">\n", pair.id));
+            tmx.push_str(&format!("      <prop type=\"x-provider\">{}</prop>\n", pair.provider));
+            if let Some(glossary) = &pair.glossary {
+                tmx.push_str(&format!("      <prop type=\"x-glossary\">{}</prop>\n", xml_escape(glossary)));
+            }
+            tmx.push_str(&format!("      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n", source, xml_escape(&pair.source)));
+            tmx.push_str(&format!("      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n", xml_escape(&self.target_language), xml_escape(&pair.target)));
+            tmx.push_str("    </tu>\n");
+        }
+        tmx.push_str("  </body>\n</tmx>\n");
+        tmx
+    }
+
+    /// Write the corpus to a file in a format, replacing the file if it exists.  Returns the path written.
+    pub fn write(&self, path: impl AsRef<Path>, format: ParallelFormat) -> Result<PathBuf, GeneratorError> {
+        OutputWriter::new()
+            .set_mode(WriteMode::Overwrite)
+            .set_create_dirs(true)
+            .write(path, &self.render(format), &Metadata::new().set_language(self.source_language).set_seed(self.seed))
+    }
+}
+
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Generate source sentences from a seed and translate each one.  Providers are tried in order for every sentence, so later ones act as fallbacks, e.g. MyMemory when the DeepL quota runs out.  If every provider fails on a sentence, the last error is returned.
+pub fn build_parallel_corpus(providers: &[Provider], options: &ParallelOptions, seed: u64) -> Result<ParallelCorpus, Box<dyn Error>> {
+    if providers.is_empty() {
+        return Err(Box::new(GeneratorError::InvalidRange(String::from("at least one provider is needed"))));
+    }
+
+    let sentences = stream::sentences(Corpus::Embedded(options.source), &options.paragraph, seeded_rng(seed))?;
+    let mut pairs = Vec::new();
+    for (i, source) in sentences.take(options.sentences).enumerate() {
+        let mut last_error = None;
+        for provider in providers {
+            match provider.translate(&source, options.source, &options.target) {
+                Ok(target) => {
+                    pairs.push(SentencePair {
+                        id: i + 1,
+                        source: source.clone(),
+                        target,
+                        provider: provider.name(),
+                        glossary: provider.glossary().map(|g| g.to_owned()),
+                    });
+                    last_error = None;
+                    break;
+                },
+                Err(e) => last_error = Some(e),
+            }
+        }
+        if let Some(e) = last_error {
+            return Err(e);
+        }
+    }
+
+    Ok(ParallelCorpus { source_language: options.source, target_language: options.target.clone(), seed, pairs })
+}
@@ -0,0 +1,326 @@
+//! Pseudo-localization for i18n testing.  English UI strings are turned into accented, length-expanded, bracketed versions such as "[Ŝéţţîñĝš ~~~]", which stay readable while showing untranslated strings, truncated layouts and broken encodings at a glance.
+//!
+//! ICU message arguments such as `{name}` or `{count, plural, one {# file} other {# files}}` and printf conversions such as `%s`, `%1$d` or `%(name)s` are left alone, while the text inside plural and select branches is still transformed.  Nothing is sent over the network.
+
+/// Accented lookalikes of the ASCII letters, lowercase then uppercase.
+const ACCENTED_LOWER: [char; 26] = ['á', 'ƀ', 'ç', 'ð', 'é', 'ƒ', 'ĝ', 'ĥ', 'î', 'ĵ', 'ķ', 'ļ', 'ɱ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š', 'ţ', 'û', 'ṽ', 'ŵ', 'ẋ', 'ý', 'ž'];
+const ACCENTED_UPPER: [char; 26] = ['Å', 'Ɓ', 'Ç', 'Đ', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ļ', 'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Ŝ', 'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ', 'Ý', 'Ž'];
+
+/// Right-to-left override and pop directional formatting, which make a run of text display right to left.
+const RLO: char = '\u{202E}';
+const PDF: char = '\u{202C}';
+
+/// How strings are pseudo-localized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PseudoLocale {
+    /// Whether ASCII letters are replaced by accented lookalikes
+    accents: bool,
+
+    /// Padding added to the end of each string, as a percentage of its translatable length
+    expansion: u32,
+
+    /// Whether the string is wrapped in square brackets, so truncation is easy to spot
+    brackets: bool,
+
+    /// Whether translatable text is displayed right to left, to test right-to-left layouts
+    mirror: bool,
+}
+
+impl Default for PseudoLocale {
+    fn default() -> Self {
+        PseudoLocale { accents: true, expansion: 30, brackets: true, mirror: false }
+    }
+}
+
+/// A run of a string: text to transform, or a placeholder to copy as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Piece {
+    Text(String),
+    Placeholder(String),
+}
+
+impl PseudoLocale {
+    /// Accented, bracketed strings expanded by 30%.
+    pub fn new() -> PseudoLocale {
+        PseudoLocale::default()
+    }
+
+    /// Replace ASCII letters with accented lookalikes, or keep them.
+    pub fn set_accents(mut self, accents: bool) -> PseudoLocale {
+        self.accents = accents;
+        self
+    }
+
+    /// Set the padding added to each string, as a percentage of its length.  German and Finnish often run 30 to 40% longer than English.
+    pub fn set_expansion(mut self, percent: u32) -> PseudoLocale {
+        self.expansion = percent;
+        self
+    }
+
+    /// Wrap strings in square brackets, or leave them unwrapped.
+    pub fn set_brackets(mut self, brackets: bool) -> PseudoLocale {
+        self.brackets = brackets;
+        self
+    }
+
+    /// Display translatable text right to left, with each run wrapped in a right-to-left override.  Placeholders stay outside the overrides.
+    pub fn set_mirror(mut self, mirror: bool) -> PseudoLocale {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Pseudo-localize one string.
+    pub fn transform(&self, text: &str) -> String {
+        let pieces = split(text);
+        let length: usize = pieces.iter()
+            .map(|p| match p {
+                Piece::Text(t) => t.chars().count(),
+                Piece::Placeholder(_) => 0,
+            })
+            .sum();
+
+        let mut result = String::new();
+        if self.brackets {
+            result.push('[');
+        }
+        for piece in pieces {
+            match piece {
+                Piece::Placeholder(p) => result.push_str(&p),
+                Piece::Text(t) => {
+                    if self.mirror {
+                        result.push(RLO);
+                    }
+                    result.extend(t.chars().map(|c| if self.accents { accent(c) } else { c }));
+                    if self.mirror {
+                        result.push(PDF);
+                    }
+                },
+            }
+        }
+
+        let padding = (length * self.expansion as usize).div_ceil(100);
+        if padding > 0 {
+            result.push(' ');
+            result.push_str(&"~".repeat(padding));
+        }
+        if self.brackets {
+            result.push(']');
+        }
+        result
+    }
+}
+
+/// Pseudo-localize a string with the default settings: accented, bracketed and 30% longer.
+pub fn pseudo_localize(text: &str) -> String {
+    PseudoLocale::new().transform(text)
+}
+
+fn accent(c: char) -> char {
+    match c {
+        'a'..='z' => ACCENTED_LOWER[(c as u8 - b'a') as usize],
+        'A'..='Z' => ACCENTED_UPPER[(c as u8 - b'A') as usize],
+        _ => c,
+    }
+}
+
+/// Split a string into text and placeholders.
+pub(crate) fn split(text: &str) -> Vec<Piece> {
+    let mut splitter = Splitter { text, pos: 0, pieces: Vec::new() };
+    splitter.message(false);
+    // a stray closing brace at the top level is just text
+    while splitter.pos < text.len() {
+        splitter.push_text("}");
+        splitter.pos += 1;
+        splitter.message(false);
+    }
+    splitter.pieces
+}
+
+/// Walks an ICU message, which may hold printf conversions too.
+struct Splitter<'a> {
+    text: &'a str,
+    pos: usize,
+    pieces: Vec<Piece>,
+}
+
+impl<'a> Splitter<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn push_text(&mut self, s: &str) {
+        match self.pieces.last_mut() {
+            Some(Piece::Text(t)) => t.push_str(s),
+            _ => self.pieces.push(Piece::Text(s.to_owned())),
+        }
+    }
+
+    fn push_placeholder(&mut self, s: &str) {
+        match self.pieces.last_mut() {
+            Some(Piece::Placeholder(p)) => p.push_str(s),
+            _ => self.pieces.push(Piece::Placeholder(s.to_owned())),
+        }
+    }
+
+    /// Copy the next `len` bytes as a placeholder.
+    fn protect(&mut self, len: usize) {
+        let s = &self.text[self.pos..self.pos + len];
+        self.push_placeholder(s);
+        self.pos += len;
+    }
+
+    /// Read message text up to the `}` closing a nested message, or to the end.  Within a plural branch `#` stands for the number.
+    fn message(&mut self, plural: bool) {
+        while let Some(c) = self.rest().chars().next() {
+            match c {
+                '}' => return,
+                '{' => {
+                    if !self.argument(plural) {
+                        self.push_text("{");
+                        self.pos += 1;
+                    }
+                },
+                '#' if plural => self.protect(1),
+                '%' => match printf_len(self.rest()) {
+                    Some(len) => self.protect(len),
+                    None => {
+                        self.push_text("%");
+                        self.pos += 1;
+                    }
+                },
+                c => {
+                    let s = &self.text[self.pos..self.pos + c.len_utf8()];
+                    self.push_text(s);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Read an ICU argument starting at `{`.  Returns false, having read nothing, if it is malformed.
+    fn argument(&mut self, plural: bool) -> bool {
+        let (start, pieces) = (self.pos, self.pieces.clone());
+        if self.try_argument(plural) {
+            return true;
+        }
+
+        self.pos = start;
+        self.pieces = pieces;
+        false
+    }
+
+    fn try_argument(&mut self, plural: bool) -> bool {
+        let rest = self.rest();
+        let header_end = match rest.find(['}', ',']) {
+            Some(i) if is_name(rest[1..i].trim()) => i,
+            _ => return false,
+        };
+
+        // {name}
+        if rest.as_bytes()[header_end] == b'}' {
+            self.protect(header_end + 1);
+            return true;
+        }
+
+        let after_name = &rest[header_end + 1..];
+        let kind_end = after_name.find([',', '}']).unwrap_or(after_name.len());
+        let kind = after_name[..kind_end].trim();
+        let branching = matches!(kind, "plural" | "select" | "selectordinal");
+        if !branching {
+            // {n, number} or {d, date, short}: copy up to the matching brace
+            let mut nesting = 0;
+            for (i, c) in rest.char_indices() {
+                match c {
+                    '{' => nesting += 1,
+                    '}' if nesting == 1 => {
+                        self.protect(i + 1);
+                        return true;
+                    },
+                    '}' => nesting -= 1,
+                    _ => ()
+                }
+            }
+            return false;
+        }
+        if after_name.as_bytes().get(kind_end) != Some(&b',') {
+            return false;
+        }
+
+        // {count, plural, offset:1 one {...} other {...}}
+        self.protect(header_end + 1 + kind_end + 1);
+        loop {
+            let rest = self.rest();
+            let selector_end = match rest.find(['{', '}']) {
+                Some(i) => i,
+                None => return false,
+            };
+            if rest.as_bytes()[selector_end] == b'}' {
+                if !rest[..selector_end].trim().is_empty() {
+                    return false;
+                }
+                self.protect(selector_end + 1);
+                return true;
+            }
+
+            self.protect(selector_end + 1);
+            self.message(plural || kind != "select");
+            if !self.rest().starts_with('}') {
+                return false;
+            }
+            self.protect(1);
+        }
+    }
+}
+
+/// ICU argument names are a single word, such as `count` or `0`, so braces around prose are left as text.
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// The length in bytes of the printf conversion at the start of `s`, if there is one.  Covers `%%`, `%s`, `%1$d`, `%-5.2f`, `%lld`, `%@`, Python's `%(name)s` and Ruby's `%{name}`.
+fn printf_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let mut i = 1;
+    if b.get(1) == Some(&b'%') {
+        return Some(2);
+    }
+    if b.get(1) == Some(&b'{') {
+        return s.find('}').map(|end| end + 1);
+    }
+
+    if b.get(1) == Some(&b'(') {
+        i = s.find(')')? + 1;
+    } else {
+        let digits = b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && b.get(i + digits) == Some(&b'$') {
+            i += digits + 1;
+        }
+    }
+    while b.get(i).is_some_and(|c| b"-+#0'".contains(c)) {
+        i += 1;
+    }
+    if b.get(i) == Some(&b'*') {
+        i += 1;
+    } else {
+        i += b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    }
+    if b.get(i) == Some(&b'.') {
+        i += 1;
+        if b.get(i) == Some(&b'*') {
+            i += 1;
+        } else {
+            i += b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        }
+    }
+    for length in ["hh", "ll", "h", "l", "L", "z", "j", "t", "q"] {
+        if s[i..].starts_with(length) {
+            i += length.len();
+            break;
+        }
+    }
+
+    match b.get(i) {
+        Some(c) if b"diouxXeEfFgGaAcspn@".contains(c) => Some(i + 1),
+        _ => None
+    }
+}
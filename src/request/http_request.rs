@@ -179,7 +179,7 @@ impl<'a> HttpRequest<'a> {
         
                 Ok(d.len())
             }).unwrap();
-            transfer.perform().unwrap();
+            transfer.perform()?;
         }
 
         //check response code for potential error
@@ -0,0 +1,397 @@
+//! Build a word list from raw documents.  Plain text, Markdown and HTML are stripped of markup and split into words, the words are counted, and the vocabulary is written ranked by frequency as "word\tcount" lines, which `Corpus::FromFile` reads with their counts.
+//!
+//! Words are found at Unicode word boundaries.  Scripts written without spaces, such as Chinese, Japanese and Thai, are split where the script changes instead, keeping kanji together with the kana that follow them.  Without a dictionary, word boundaries in those scripts are approximate.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::output::{Metadata, OutputWriter, WriteMode};
+use super::stats::Script;
+use super::vocabulary::Vocabulary;
+
+/// The markup of a source document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceFormat {
+    /// Plain text
+    #[default]
+    Text,
+
+    /// Markdown.  Code blocks, inline code and link targets are skipped.
+    Markdown,
+
+    /// HTML.  Tags, comments, scripts and styles are skipped and character references are decoded.
+    Html,
+}
+
+impl SourceFormat {
+    /// The format of a file, going by its extension.  Files that are neither Markdown nor HTML are read as plain text.
+    pub fn from_path(path: impl AsRef<Path>) -> SourceFormat {
+        let extension = path.as_ref().extension().map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("md") | Some("markdown") => SourceFormat::Markdown,
+            Some("html") | Some("htm") | Some("xhtml") => SourceFormat::Html,
+            _ => SourceFormat::Text,
+        }
+    }
+}
+
+/// Counts the words of source documents and ranks them into a word list.
+#[derive(Clone, Debug)]
+pub struct CorpusBuilder {
+    counts: HashMap<String, u64>,
+
+    /// Words seen fewer times than this are left out
+    min_count: u64,
+
+    /// Only the most frequent words are kept, if set
+    max_words: Option<usize>,
+
+    /// Whether tokens without a letter, such as "2023" or "3.14", are kept
+    keep_numbers: bool,
+
+    /// Whether URLs and email addresses are kept, each as a single word
+    keep_urls: bool,
+
+    /// Whether words are lowercased, so that "The" and "the" are counted together
+    lowercase: bool,
+
+    /// Lowercase words to leave out
+    stopwords: HashSet<String>,
+}
+
+impl Default for CorpusBuilder {
+    fn default() -> Self {
+        CorpusBuilder {
+            counts: HashMap::new(),
+            min_count: 1,
+            max_words: None,
+            keep_numbers: false,
+            keep_urls: false,
+            lowercase: true,
+            stopwords: HashSet::new(),
+        }
+    }
+}
+
+impl CorpusBuilder {
+    /// A builder that lowercases words, drops numbers and URLs and keeps every other word.
+    pub fn new() -> CorpusBuilder {
+        CorpusBuilder::default()
+    }
+
+    /// Leave out words seen fewer than `n` times.
+    pub fn set_min_count(mut self, n: u64) -> CorpusBuilder {
+        self.min_count = n;
+        self
+    }
+
+    /// Keep only the `n` most frequent words.
+    pub fn set_max_words(mut self, n: usize) -> CorpusBuilder {
+        self.max_words = Some(n);
+        self
+    }
+
+    /// Keep or drop tokens without a letter, such as "2023" or "3.14".
+    pub fn set_keep_numbers(mut self, keep: bool) -> CorpusBuilder {
+        self.keep_numbers = keep;
+        self
+    }
+
+    /// Keep URLs and email addresses, each as a single word, or drop them.
+    pub fn set_keep_urls(mut self, keep: bool) -> CorpusBuilder {
+        self.keep_urls = keep;
+        self
+    }
+
+    /// Lowercase words, or keep their case.  Keep the case for languages that capitalize nouns, such as German.
+    pub fn set_lowercase(mut self, lowercase: bool) -> CorpusBuilder {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Set the words to leave out.  They are compared without regard to case.
+    pub fn set_stopwords<S: AsRef<str>>(mut self, stopwords: &[S]) -> CorpusBuilder {
+        self.stopwords = stopwords.iter().map(|w| w.as_ref().trim().to_lowercase()).filter(|w| !w.is_empty()).collect();
+        self
+    }
+
+    /// Count the words of a document.
+    pub fn add_document(&mut self, text: &str, format: SourceFormat) {
+        let text = match format {
+            SourceFormat::Text => text.to_owned(),
+            SourceFormat::Markdown => strip_markdown(text),
+            SourceFormat::Html => strip_html(text),
+        };
+
+        for chunk in text.split_whitespace() {
+            if is_url(chunk) {
+                if self.keep_urls {
+                    let url = chunk.trim_start_matches(['<', '(', '"', '\''])
+                        .trim_end_matches(['>', ')', '"', '\'', '.', ',', ';', ':', '!', '?']);
+                    self.count(url.to_owned());
+                }
+                continue;
+            }
+
+            for token in tokenize(chunk) {
+                if !self.keep_numbers && !token.chars().any(char::is_alphabetic) {
+                    continue;
+                }
+                let word = if self.lowercase { token.to_lowercase() } else { token.to_owned() };
+                if !self.stopwords.contains(&word.to_lowercase()) {
+                    self.count(word);
+                }
+            }
+        }
+    }
+
+    /// Count the words of a file, reading it as Markdown, HTML or plain text by its extension.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<(), GeneratorError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => GeneratorError::MissingCorpus(path.display().to_string()),
+            _ => GeneratorError::Io(e)
+        })?;
+        self.add_document(&text, SourceFormat::from_path(path));
+        Ok(())
+    }
+
+    /// Count the words of every file in a directory and its subdirectories.
+    pub fn add_dir(&mut self, path: impl AsRef<Path>) -> Result<(), GeneratorError> {
+        let path = path.as_ref();
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => GeneratorError::MissingCorpus(path.display().to_string()),
+                _ => GeneratorError::Io(e)
+            })?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        entries.sort();
+
+        for entry in entries {
+            if entry.is_dir() {
+                self.add_dir(&entry)?;
+            } else {
+                self.add_file(&entry)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn count(&mut self, word: String) {
+        *self.counts.entry(word).or_insert(0) += 1;
+    }
+
+    /// The words seen at least `min_count` times with their counts, most frequent first.  Words seen equally often are in alphabetical order.
+    pub fn ranked(&self) -> Vec<(String, u64)> {
+        let mut ranked: Vec<(String, u64)> = self.counts.iter()
+            .filter(|(_, &n)| n >= self.min_count)
+            .map(|(w, &n)| (w.clone(), n))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        if let Some(n) = self.max_words {
+            ranked.truncate(n);
+        }
+        ranked
+    }
+
+    /// The ranked word list as "word\tcount" lines.
+    pub fn to_word_list(&self) -> String {
+        self.ranked().iter().map(|(w, n)| format!("{}\t{}\n", w, n)).collect()
+    }
+
+    /// Write the ranked word list to a file, replacing it if it exists.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<PathBuf, GeneratorError> {
+        if self.ranked().is_empty() {
+            return Err(GeneratorError::EmptyCorpus);
+        }
+        OutputWriter::new()
+            .set_mode(WriteMode::Overwrite)
+            .write(path, &self.to_word_list(), &Metadata::new())
+    }
+
+    /// Load the ranked word list as a vocabulary, with its counts, without writing it out.
+    pub fn build(&self) -> Result<Vocabulary, GeneratorError> {
+        let lines = self.ranked().into_iter().map(|(w, n)| format!("{}\t{}", w, n)).collect();
+        Vocabulary::from_lines(lines, None)
+    }
+}
+
+/// Scripts whose words are not separated by spaces.
+fn unspaced(script: Script) -> bool {
+    matches!(script, Script::Han | Script::Hiragana | Script::Katakana | Script::Thai)
+}
+
+/// Whether a token in the second script continues a word in the first: runs of one script, and kanji followed by kana.
+fn continues(previous: Script, next: Script) -> bool {
+    previous == next || (previous == Script::Han && next == Script::Hiragana)
+}
+
+/// The words of a run of text without whitespace.
+fn tokenize(chunk: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = Vec::new();
+    // byte range and script of the last token, while it can still be extended
+    let mut open: Option<(usize, usize, Script)> = None;
+
+    for (start, token) in chunk.split_word_bound_indices() {
+        if !token.chars().any(char::is_alphanumeric) {
+            if let Some((s, e, _)) = open.take() {
+                tokens.push(&chunk[s..e]);
+            }
+            continue;
+        }
+
+        let script = token.chars().map(Script::of).find(|s| !matches!(s, Script::Common | Script::Inherited)).unwrap_or(Script::Common);
+        open = match open {
+            Some((s, e, previous)) if e == start && unspaced(script) && unspaced(previous) && continues(previous, script) => Some((s, start + token.len(), script)),
+            Some((s, e, _)) => {
+                tokens.push(&chunk[s..e]);
+                Some((start, start + token.len(), script))
+            },
+            None => Some((start, start + token.len(), script))
+        };
+    }
+    if let Some((s, e, _)) = open {
+        tokens.push(&chunk[s..e]);
+    }
+
+    tokens
+}
+
+fn is_url(chunk: &str) -> bool {
+    let chunk = chunk.trim_start_matches(['<', '(', '"', '\'']);
+    chunk.contains("://") || chunk.starts_with("www.") || chunk.starts_with("mailto:")
+        || chunk.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+/// Markdown without code and link targets.  Emphasis, heading and list markers are left for the tokenizer to skip.
+fn strip_markdown(text: &str) -> String {
+    let mut prose = String::new();
+    let mut fenced = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+
+        // drop inline code, and the target of links and images
+        let mut rest = line;
+        while let Some(i) = rest.find(['`', ']']) {
+            prose.push_str(&rest[..i]);
+            let marker = &rest[i..];
+            if let Some(code) = marker.strip_prefix('`') {
+                rest = code.find('`').map_or("", |end| &code[end + 1..]);
+            } else if let Some(target) = marker.strip_prefix("](") {
+                rest = target.find(')').map_or("", |end| &target[end + 1..]);
+            } else {
+                rest = &marker[1..];
+            }
+            prose.push(' ');
+        }
+        prose.push_str(rest);
+        prose.push('\n');
+    }
+    strip_html(&prose)
+}
+
+/// The text of an HTML document, without tags, comments, scripts or styles, and with character references decoded.
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(i) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..i]));
+        text.push(' ');
+        let tag = &rest[i..];
+
+        let lower: String = tag.chars().take(8).collect::<String>().to_ascii_lowercase();
+        let skip_to = if tag.starts_with("<!--") {
+            "-->"
+        } else if lower.starts_with("<script") {
+            "</script>"
+        } else if lower.starts_with("<style") {
+            "</style>"
+        } else {
+            ">"
+        };
+        rest = match find_ignoring_case(tag, skip_to) {
+            Some(end) => &tag[end + skip_to.len()..],
+            None => ""
+        };
+    }
+    text.push_str(&decode_entities(rest));
+    text
+}
+
+fn find_ignoring_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| haystack[i..].get(..needle.len()).is_some_and(|s| s.eq_ignore_ascii_case(needle)))
+}
+
+/// Decode numeric character references and the common named ones.  Unknown references are left as written.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        decoded.push_str(&rest[..i]);
+        let reference = &rest[i + 1..];
+        let end = reference.find(';').filter(|&end| end <= 10);
+        let character = end.and_then(|end| {
+            let name = &reference[..end];
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => name.strip_prefix('#').and_then(|n| n.parse().ok())
+            };
+            match code {
+                Some(code) => char::from_u32(code),
+                None => named_entity(name)
+            }
+        });
+
+        match (character, end) {
+            (Some(c), Some(end)) => {
+                decoded.push(c);
+                rest = &reference[end + 1..];
+            },
+            _ => {
+                decoded.push('&');
+                rest = reference;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Named references for markup characters, punctuation and the accented letters of Latin-1.  Capitalized names, such as "Eacute", give capital letters.
+const ENTITIES: [(&str, char); 50] = [
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", '\u{A0}'),
+    ("ndash", '–'), ("mdash", '—'), ("hellip", '…'), ("lsquo", '‘'), ("rsquo", '’'), ("ldquo", '“'), ("rdquo", '”'),
+    ("laquo", '«'), ("raquo", '»'), ("iexcl", '¡'), ("iquest", '¿'),
+    ("agrave", 'à'), ("aacute", 'á'), ("acirc", 'â'), ("atilde", 'ã'), ("auml", 'ä'), ("aring", 'å'), ("aelig", 'æ'),
+    ("ccedil", 'ç'), ("egrave", 'è'), ("eacute", 'é'), ("ecirc", 'ê'), ("euml", 'ë'), ("igrave", 'ì'), ("iacute", 'í'),
+    ("icirc", 'î'), ("iuml", 'ï'), ("eth", 'ð'), ("ntilde", 'ñ'), ("ograve", 'ò'), ("oacute", 'ó'), ("ocirc", 'ô'),
+    ("otilde", 'õ'), ("ouml", 'ö'), ("oslash", 'ø'), ("ugrave", 'ù'), ("uacute", 'ú'), ("ucirc", 'û'), ("uuml", 'ü'),
+    ("yacute", 'ý'), ("thorn", 'þ'), ("yuml", 'ÿ'), ("szlig", 'ß'), ("oelig", 'œ'),
+];
+
+fn named_entity(name: &str) -> Option<char> {
+    if let Some(&(_, c)) = ENTITIES.iter().find(|(n, _)| *n == name) {
+        return Some(c);
+    }
+    // "Eacute" and "AElig" are the capitals of "eacute" and "aelig"
+    let lower = name.to_ascii_lowercase();
+    match ENTITIES.iter().find(|(n, c)| *n == lower && c.is_alphabetic() && *c != 'ß') {
+        Some(&(_, c)) if name.starts_with(|f: char| f.is_ascii_uppercase()) => c.to_uppercase().next(),
+        _ => None
+    }
+}
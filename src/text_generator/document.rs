@@ -0,0 +1,483 @@
+//! Structured placeholder documents.  A document is a tree of a title, sections and subsections holding paragraphs, lists, quotes and links, and can be rendered to Markdown, HTML or plain text.
+
+use rand::Rng;
+
+use super::error::GeneratorError;
+use super::language::Language;
+use super::typography::{Direction, Typography};
+use super::{assemble_sentence, build_paragraph, load_source, make_sentence, probability, random_index, seeded_rng, Corpus, ParagraphOptions, Source};
+
+/// A run of text within a paragraph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inline {
+    /// Plain text
+    Text(String),
+
+    /// A hyperlink
+    Link { text: String, href: String },
+}
+
+/// A block of content within a section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// A paragraph, possibly containing links
+    Paragraph(Vec<Inline>),
+
+    /// An unordered list of items
+    BulletList(Vec<String>),
+
+    /// An ordered list of items
+    NumberedList(Vec<String>),
+
+    /// A block quote
+    Quote(String),
+}
+
+/// A section with a heading, its content and any nested subsections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub heading: String,
+    pub blocks: Vec<Block>,
+    pub subsections: Vec<Section>,
+}
+
+/// A generated document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    pub title: String,
+
+    /// The language of the corpus the document was generated from, if known
+    pub language: Option<Language>,
+
+    /// The direction of the script the document is written in
+    pub direction: Direction,
+
+    /// Content between the title and the first section
+    pub introduction: Vec<Block>,
+
+    pub sections: Vec<Section>,
+}
+
+/// Options that shape a generated document.
+#[derive(Clone, Debug)]
+pub struct DocumentOptions {
+    /// Number of top-level sections
+    sections: usize,
+
+    /// Each section gets between 0 and this many subsections
+    max_subsections: usize,
+
+    /// Each section gets between 1 and this many paragraphs
+    max_paragraphs: usize,
+
+    /// Chance that a section has a bulleted or numbered list
+    list_probability: f64,
+
+    /// Chance that a section has a block quote
+    quote_probability: f64,
+
+    /// Chance that a paragraph contains a link
+    link_probability: f64,
+
+    /// Options for every paragraph in the document
+    paragraph: ParagraphOptions,
+}
+
+impl Default for DocumentOptions {
+    fn default() -> Self {
+        DocumentOptions {
+            sections: 3,
+            max_subsections: 2,
+            max_paragraphs: 3,
+            list_probability: 0.5,
+            quote_probability: 0.3,
+            link_probability: 0.3,
+            paragraph: ParagraphOptions::default(),
+        }
+    }
+}
+
+impl DocumentOptions {
+    /// Three sections with up to two subsections and three paragraphs each.
+    pub fn new() -> DocumentOptions {
+        DocumentOptions::default()
+    }
+
+    /// Set the number of top-level sections.
+    pub fn set_sections(mut self, n: usize) -> DocumentOptions {
+        self.sections = n;
+        self
+    }
+
+    /// Set the maximum number of subsections in a section.
+    pub fn set_max_subsections(mut self, n: usize) -> DocumentOptions {
+        self.max_subsections = n;
+        self
+    }
+
+    /// Set the maximum number of paragraphs in a section.  At least one paragraph is always generated.
+    pub fn set_max_paragraphs(mut self, n: usize) -> DocumentOptions {
+        self.max_paragraphs = n.max(1);
+        self
+    }
+
+    /// Set the chance, from 0 to 1, that a section has a list.
+    pub fn set_list_probability(mut self, p: f64) -> DocumentOptions {
+        self.list_probability = probability(p);
+        self
+    }
+
+    /// Set the chance, from 0 to 1, that a section has a block quote.
+    pub fn set_quote_probability(mut self, p: f64) -> DocumentOptions {
+        self.quote_probability = probability(p);
+        self
+    }
+
+    /// Set the chance, from 0 to 1, that a paragraph contains a link.
+    pub fn set_link_probability(mut self, p: f64) -> DocumentOptions {
+        self.link_probability = probability(p);
+        self
+    }
+
+    /// Set the options used for every paragraph.
+    pub fn set_paragraph_options(mut self, options: ParagraphOptions) -> DocumentOptions {
+        self.paragraph = options;
+        self
+    }
+}
+
+/// Generate a document drawing all randomness from the caller's random number generator.
+pub fn generate_document<R: Rng + ?Sized>(corpus: Corpus, options: &DocumentOptions, rng: &mut R) -> Result<Document, GeneratorError> {
+    options.paragraph.validate()?;
+    if options.max_subsections == usize::MAX {
+        return Err(GeneratorError::InvalidRange(String::from("max_subsections is too large")));
+    }
+
+    let language = corpus.language();
+    let typography = options.paragraph.typography_for(language);
+    let source = load_source(corpus, &options.paragraph.sampling, options.paragraph.reading_level.as_ref())?;
+
+    let mut builder = Builder { source, typography, options, rng, links: 0 };
+
+    let title = builder.phrase(6);
+    let introduction = vec![builder.paragraph()];
+    let sections = (0..options.sections).map(|_| builder.section(true)).collect();
+
+    Ok(Document { title, language, direction: builder.typography.direction, introduction, sections })
+}
+
+/// Generate a document from a seed.  The same seed, corpus and options always produce the same document.
+pub fn generate_document_from_seed(corpus: Corpus, options: &DocumentOptions, seed: u64) -> Result<Document, GeneratorError> {
+    generate_document(corpus, options, &mut seeded_rng(seed))
+}
+
+/// Holds what is needed while a document tree is being generated.
+struct Builder<'o, 'r, R: Rng + ?Sized> {
+    source: Source,
+    typography: Typography,
+    options: &'o DocumentOptions,
+    rng: &'r mut R,
+
+    /// Number of links generated so far, used to give each link its own address
+    links: usize,
+}
+
+impl<'o, 'r, R: Rng + ?Sized> Builder<'o, 'r, R> {
+    /// A capitalized phrase of 2 to `max_words` words without a full stop, for titles, headings and list items.
+    fn phrase(&mut self, max_words: usize) -> String {
+        let mut words = self.source.sentence_words(self.rng);
+        words.truncate(2 + random_index(self.rng, max_words.max(2) - 1));
+
+        let sentence = assemble_sentence(&words, &self.typography);
+        match sentence.strip_suffix(self.typography.full_stop) {
+            Some(s) => s.to_owned(),
+            None => sentence,
+        }
+    }
+
+    fn section(&mut self, top_level: bool) -> Section {
+        let heading = self.phrase(5);
+
+        let mut blocks = Vec::new();
+        for _ in 0..1 + random_index(self.rng, self.options.max_paragraphs) {
+            blocks.push(self.paragraph());
+        }
+        if self.rng.gen_bool(self.options.list_probability) {
+            let items = (0..2 + random_index(self.rng, 4)).map(|_| self.phrase(6)).collect();
+            let list = if self.rng.gen_bool(0.5) { Block::BulletList(items) } else { Block::NumberedList(items) };
+            blocks.insert(random_index(self.rng, blocks.len() + 1), list);
+        }
+        if self.rng.gen_bool(self.options.quote_probability) {
+            let quote = self.source.sentence_words(self.rng);
+            let quote = Block::Quote(make_sentence(&quote, &self.typography, &self.options.paragraph, self.rng, true));
+            blocks.insert(random_index(self.rng, blocks.len() + 1), quote);
+        }
+
+        let mut subsections = Vec::new();
+        if top_level {
+            for _ in 0..random_index(self.rng, self.options.max_subsections + 1) {
+                subsections.push(self.section(false));
+            }
+        }
+
+        Section { heading, blocks, subsections }
+    }
+
+    fn paragraph(&mut self) -> Block {
+        let text = build_paragraph(&self.source, &self.typography, &self.options.paragraph, self.rng);
+
+        if self.rng.gen_bool(self.options.link_probability) {
+            if let Some((start, end)) = self.link_span(&text) {
+                self.links += 1;
+                let link = Inline::Link {
+                    text: text[start..end].to_owned(),
+                    href: format!("https://example.com/page-{}", self.links),
+                };
+
+                let mut inlines = Vec::new();
+                if start > 0 {
+                    inlines.push(Inline::Text(text[..start].to_owned()));
+                }
+                inlines.push(link);
+                if end < text.len() {
+                    inlines.push(Inline::Text(text[end..].to_owned()));
+                }
+
+                return Block::Paragraph(inlines);
+            }
+        }
+
+        Block::Paragraph(vec![Inline::Text(text)])
+    }
+
+    /// Pick a run of one to three words, or two to four characters in scripts written without spaces, that holds no punctuation.
+    fn link_span(&mut self, text: &str) -> Option<(usize, usize)> {
+        // byte ranges of the units a link can be made of
+        let units: Vec<(usize, usize)> = if self.typography.word_separator.is_empty() {
+            text.char_indices().map(|(i, c)| (i, i + c.len_utf8())).collect()
+        } else {
+            let mut offset = 0;
+            let mut units = Vec::new();
+            for word in text.split(self.typography.word_separator) {
+                units.push((offset, offset + word.len()));
+                offset += word.len() + self.typography.word_separator.len();
+            }
+            units
+        };
+        if units.is_empty() {
+            return None;
+        }
+
+        let (min_len, extra) = if self.typography.word_separator.is_empty() { (2, 3) } else { (1, 3) };
+        for _ in 0..10 {
+            let len = min_len + random_index(self.rng, extra);
+            let start = random_index(self.rng, units.len());
+            if start + len > units.len() {
+                continue;
+            }
+
+            let span = (units[start].0, units[start + len - 1].1);
+            let candidate = &text[span.0..span.1];
+            if !candidate.is_empty() && !candidate.chars().any(is_punctuation) {
+                return Some(span);
+            }
+        }
+
+        None
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || "。、！？।،؟؛«»¿¡「」（）；„“”‘’…—–".contains(c)
+}
+
+impl Document {
+    /// Render the document as Markdown.  Characters Markdown would read as markup are escaped with a backslash.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", escape_markdown(&self.title));
+        blocks_to_markdown(&self.introduction, &mut out);
+        for section in &self.sections {
+            section_to_markdown(section, 2, &mut out);
+        }
+
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    /// Render the document as a standalone HTML page.  The `lang` and `dir` attributes come from the corpus language and its script.
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<!DOCTYPE html>\n<html");
+        if let Some(language) = self.language {
+            out.push_str(&format!(" lang=\"{}\"", language.code()));
+        }
+        if self.direction == Direction::Rtl {
+            out.push_str(" dir=\"rtl\"");
+        }
+        out.push_str(">\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n</head>\n<body>\n", escape_html(&self.title)));
+        out.push_str(&format!("<h1>{}</h1>\n", escape_html(&self.title)));
+
+        blocks_to_html(&self.introduction, &mut out);
+        for section in &self.sections {
+            section_to_html(section, 2, &mut out);
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Render the document as plain text.  Links keep their text and drop their address.
+    pub fn to_plain_text(&self) -> String {
+        let mut out = format!("{}\n\n", self.title);
+        blocks_to_plain_text(&self.introduction, &mut out);
+        for section in &self.sections {
+            section_to_plain_text(section, &mut out);
+        }
+
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+}
+
+fn section_to_markdown(section: &Section, level: usize, out: &mut String) {
+    out.push_str(&format!("{} {}\n\n", "#".repeat(level), escape_markdown(&section.heading)));
+    blocks_to_markdown(&section.blocks, out);
+    for subsection in &section.subsections {
+        section_to_markdown(subsection, level + 1, out);
+    }
+}
+
+fn blocks_to_markdown(blocks: &[Block], out: &mut String) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) => {
+                for inline in inlines {
+                    match inline {
+                        Inline::Text(t) => out.push_str(&escape_markdown(t)),
+                        Inline::Link { text, href } => out.push_str(&format!("[{}]({})", escape_markdown(text), href)),
+                    }
+                }
+                out.push('\n');
+            },
+            Block::BulletList(items) => {
+                for item in items {
+                    out.push_str(&format!("- {}\n", escape_markdown(item)));
+                }
+            },
+            Block::NumberedList(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(&format!("{}. {}\n", i + 1, escape_markdown(item)));
+                }
+            },
+            Block::Quote(text) => out.push_str(&format!("> {}\n", escape_markdown(text))),
+        }
+        out.push('\n');
+    }
+}
+
+/// Backslash-escape the characters Markdown reads as markup, so words from the corpus cannot change the structure of the document.
+fn escape_markdown(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\`*_[]<>#|~&".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+
+    // at the start of a line these would begin a list or underline a heading
+    let digits = out.len() - out.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if out.starts_with(['-', '+', '=']) {
+        out.insert(0, '\\');
+    } else if digits > 0 && out[digits..].starts_with(['.', ')']) {
+        out.insert(digits, '\\');
+    }
+    out
+}
+
+fn section_to_html(section: &Section, level: usize, out: &mut String) {
+    // HTML has no headings below h6
+    let level = level.min(6);
+    out.push_str(&format!("<section>\n<h{}>{}</h{}>\n", level, escape_html(&section.heading), level));
+    blocks_to_html(&section.blocks, out);
+    for subsection in &section.subsections {
+        section_to_html(subsection, level + 1, out);
+    }
+    out.push_str("</section>\n");
+}
+
+fn blocks_to_html(blocks: &[Block], out: &mut String) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) => {
+                out.push_str("<p>");
+                for inline in inlines {
+                    match inline {
+                        Inline::Text(t) => out.push_str(&escape_html(t)),
+                        Inline::Link { text, href } => {
+                            out.push_str(&format!("<a href=\"{}\">{}</a>", escape_html(href), escape_html(text)))
+                        },
+                    }
+                }
+                out.push_str("</p>\n");
+            },
+            Block::BulletList(items) => list_to_html("ul", items, out),
+            Block::NumberedList(items) => list_to_html("ol", items, out),
+            Block::Quote(text) => out.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", escape_html(text))),
+        }
+    }
+}
+
+fn list_to_html(tag: &str, items: &[String], out: &mut String) {
+    out.push_str(&format!("<{}>\n", tag));
+    for item in items {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+    }
+    out.push_str(&format!("</{}>\n", tag));
+}
+
+fn section_to_plain_text(section: &Section, out: &mut String) {
+    out.push_str(&format!("{}\n\n", section.heading));
+    blocks_to_plain_text(&section.blocks, out);
+    for subsection in &section.subsections {
+        section_to_plain_text(subsection, out);
+    }
+}
+
+fn blocks_to_plain_text(blocks: &[Block], out: &mut String) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) => {
+                for inline in inlines {
+                    match inline {
+                        Inline::Text(t) => out.push_str(t),
+                        Inline::Link { text, .. } => out.push_str(text),
+                    }
+                }
+                out.push('\n');
+            },
+            Block::BulletList(items) => {
+                for item in items {
+                    out.push_str(&format!("* {}\n", item));
+                }
+            },
+            Block::NumberedList(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(&format!("{}. {}\n", i + 1, item));
+                }
+            },
+            Block::Quote(text) => out.push_str(&format!("    {}\n", text)),
+        }
+        out.push('\n');
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
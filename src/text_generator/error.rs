@@ -0,0 +1,74 @@
+//! Errors returned by the text generator.  Bad input, such as a missing or empty corpus or an option too large to handle, is reported as an error instead of a panic.
+
+use std::fmt::Display;
+
+use super::language::UnknownLanguage;
+
+/// Everything that can stop text from being generated.
+#[derive(Debug)]
+pub enum GeneratorError {
+    /// The corpus file or directory at this path does not exist
+    MissingCorpus(String),
+
+    /// The corpus holds no words, e.g. an empty file or one with only blank lines
+    EmptyCorpus,
+
+    /// An option is too large for the generator, described by the message
+    InvalidRange(String),
+
+    /// A sentence template cannot be parsed, described by the message
+    InvalidTemplate(String),
+
+    /// A template slot names a tag that no word in the corpus carries
+    UnknownTag(String),
+
+    /// An output format cannot be written in the chosen mode, e.g. appending to a JSON document
+    UnsupportedOutput(String),
+
+    /// A resource file to fill in cannot be parsed, described by the message
+    InvalidResource(String),
+
+    /// Reading the corpus or writing the generated text failed
+    Io(std::io::Error),
+
+    /// A language code is not one of the bundled languages
+    UnknownLanguage(UnknownLanguage),
+}
+
+impl Display for GeneratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeneratorError::MissingCorpus(path) => write!(f, "Corpus not found: {}", path),
+            GeneratorError::EmptyCorpus => f.write_str("The corpus holds no words"),
+            GeneratorError::InvalidRange(message) => write!(f, "Invalid range: {}", message),
+            GeneratorError::InvalidTemplate(message) => write!(f, "Invalid template: {}", message),
+            GeneratorError::UnknownTag(tag) => write!(f, "No word in the corpus is tagged {}", tag),
+            GeneratorError::UnsupportedOutput(message) => write!(f, "Unsupported output: {}", message),
+            GeneratorError::InvalidResource(message) => write!(f, "Invalid resource file: {}", message),
+            GeneratorError::Io(e) => write!(f, "I/O error: {}", e),
+            GeneratorError::UnknownLanguage(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GeneratorError::Io(e) => Some(e),
+            GeneratorError::UnknownLanguage(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for GeneratorError {
+    fn from(e: std::io::Error) -> Self {
+        GeneratorError::Io(e)
+    }
+}
+
+impl From<UnknownLanguage> for GeneratorError {
+    fn from(e: UnknownLanguage) -> Self {
+        GeneratorError::UnknownLanguage(e)
+    }
+}
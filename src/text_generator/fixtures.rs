@@ -0,0 +1,224 @@
+//! Bulk fixtures for indexing benchmarks.  Thousands of documents in the bundled languages are written into a tree of nested directories, with sizes drawn from a distribution, alongside a manifest listing every file.  Tests can check ingestion results against the manifest.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::language::Language;
+use super::output::{Metadata, OutputWriter, WriteMode};
+use super::{generate_paragraph_from_seed, random_index, seeded_rng, Corpus, ParagraphOptions};
+
+/// How the byte sizes of documents are chosen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SizeDistribution {
+    /// Every document has this many bytes.
+    Fixed(usize),
+
+    /// Sizes are spread evenly between `min` and `max` bytes, inclusive.
+    Uniform { min: usize, max: usize },
+
+    /// Each size in bytes is chosen with a chance proportional to its weight, e.g. many small files and a few large ones.
+    Weighted(Vec<(usize, f64)>),
+}
+
+/// The format of the manifest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// `manifest.csv`, with a header row
+    #[default]
+    Csv,
+
+    /// `manifest.json`, an array of objects
+    Json,
+}
+
+/// Which documents to generate and how to lay them out.
+#[derive(Clone, Debug)]
+pub struct FixtureLayout {
+    /// Number of documents
+    documents: usize,
+
+    /// Each document is in one of these languages, chosen at random
+    languages: Vec<Language>,
+
+    sizes: SizeDistribution,
+
+    /// Number of directory levels above each document
+    depth: usize,
+
+    /// Number of subdirectories in each directory
+    fanout: usize,
+
+    manifest: ManifestFormat,
+
+    /// Options for every document.  Their length target is replaced by the size drawn for the document.
+    paragraph: ParagraphOptions,
+}
+
+impl Default for FixtureLayout {
+    fn default() -> Self {
+        FixtureLayout {
+            documents: 100,
+            languages: Language::ALL.to_vec(),
+            sizes: SizeDistribution::Uniform { min: 256, max: 4096 },
+            depth: 2,
+            fanout: 4,
+            manifest: ManifestFormat::Csv,
+            paragraph: ParagraphOptions::default(),
+        }
+    }
+}
+
+impl FixtureLayout {
+    /// 100 documents of 256 bytes to 4 KiB in every bundled language, spread over two levels of four directories each.
+    pub fn new() -> FixtureLayout {
+        FixtureLayout::default()
+    }
+
+    /// Set the number of documents.
+    pub fn set_documents(mut self, n: usize) -> FixtureLayout {
+        self.documents = n;
+        self
+    }
+
+    /// Set the languages documents are written in.
+    pub fn set_languages(mut self, languages: &[Language]) -> FixtureLayout {
+        self.languages = languages.to_vec();
+        self
+    }
+
+    /// Set how document sizes are chosen.
+    pub fn set_sizes(mut self, sizes: SizeDistribution) -> FixtureLayout {
+        self.sizes = sizes;
+        self
+    }
+
+    /// Set the number of directory levels above each document.  At depth 0 every document is written to the root.
+    pub fn set_depth(mut self, depth: usize) -> FixtureLayout {
+        self.depth = depth;
+        self
+    }
+
+    /// Set the number of subdirectories in each directory.
+    pub fn set_fanout(mut self, fanout: usize) -> FixtureLayout {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Set the format of the manifest.
+    pub fn set_manifest(mut self, format: ManifestFormat) -> FixtureLayout {
+        self.manifest = format;
+        self
+    }
+
+    /// Set the options used for every document.
+    pub fn set_paragraph_options(mut self, options: ParagraphOptions) -> FixtureLayout {
+        self.paragraph = options;
+        self
+    }
+
+    fn validate(&self) -> Result<(), GeneratorError> {
+        if self.languages.is_empty() {
+            return Err(GeneratorError::InvalidRange(String::from("no languages to choose from")));
+        }
+        if self.depth > 0 && self.fanout == 0 {
+            return Err(GeneratorError::InvalidRange(String::from("fanout must be at least 1 when depth is above 0")));
+        }
+        if let SizeDistribution::Uniform { min, max } = self.sizes {
+            if min > max {
+                return Err(GeneratorError::InvalidRange(format!("minimum size {} is above maximum size {}", min, max)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One generated document, as listed in the manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FixtureEntry {
+    /// Path relative to the root directory, with `/` between components on every platform
+    pub path: String,
+
+    pub language: Language,
+
+    /// Size of the file in bytes
+    pub bytes: usize,
+
+    /// Number of words, split at Unicode word boundaries so that scripts written without spaces are counted too
+    pub words: usize,
+
+    /// The seed of the text.  `generate_paragraph_from_seed` with this seed, the document's language and the layout's options limited to `bytes` regenerates it.
+    pub seed: u64,
+}
+
+/// Generate the documents of a layout under `root` and write the manifest next to them.  The same seed and layout always produce the same files.  Existing files with the same names are replaced.
+pub fn generate_fixtures(root: impl AsRef<Path>, layout: &FixtureLayout, seed: u64) -> Result<Vec<FixtureEntry>, GeneratorError> {
+    layout.validate()?;
+    let root = root.as_ref();
+    let mut rng = seeded_rng(seed);
+    let weights = match &layout.sizes {
+        SizeDistribution::Weighted(choices) => Some(
+            WeightedIndex::new(choices.iter().map(|(_, w)| *w))
+                .map_err(|e| GeneratorError::InvalidRange(format!("size weights: {}", e)))?
+        ),
+        _ => None
+    };
+
+    let writer = OutputWriter::new().set_mode(WriteMode::Overwrite);
+    let width = layout.documents.saturating_sub(1).to_string().len();
+    let mut entries = Vec::with_capacity(layout.documents);
+
+    for i in 0..layout.documents {
+        let language = layout.languages[random_index(&mut rng, layout.languages.len())];
+        let size = match &layout.sizes {
+            SizeDistribution::Fixed(n) => *n,
+            SizeDistribution::Uniform { min, max } => rng.gen_range(*min as u64..=*max as u64) as usize,
+            SizeDistribution::Weighted(choices) => weights.as_ref().map_or(0, |w| choices[w.sample(&mut rng)].0),
+        };
+        let text_seed: u64 = rng.gen();
+
+        let mut components: Vec<String> = (0..layout.depth)
+            .map(|_| format!("d{}", random_index(&mut rng, layout.fanout)))
+            .collect();
+        components.push(format!("{:0width$}-{}.txt", i, language.code(), width = width));
+
+        let options = layout.paragraph.clone().set_max_bytes(size);
+        let text = generate_paragraph_from_seed(Corpus::Embedded(language), &options, text_seed)?;
+        let path = components.join("/");
+        writer.write(root.join(&path), &text, &Metadata::new())?;
+
+        entries.push(FixtureEntry { path, language, bytes: text.len(), words: text.unicode_words().count(), seed: text_seed });
+    }
+
+    write_manifest(root, &entries, layout.manifest)?;
+    Ok(entries)
+}
+
+/// Write the manifest to `manifest.csv` or `manifest.json` in the root directory and return its path.
+pub fn write_manifest(root: impl AsRef<Path>, entries: &[FixtureEntry], format: ManifestFormat) -> Result<PathBuf, GeneratorError> {
+    let (name, contents) = match format {
+        ManifestFormat::Csv => {
+            let mut csv = String::from("path,language,bytes,words,seed\n");
+            for e in entries {
+                csv.push_str(&format!("{},{},{},{},{}\n", csv_field(&e.path), e.language.code(), e.bytes, e.words, e.seed));
+            }
+            ("manifest.csv", csv)
+        },
+        ManifestFormat::Json => ("manifest.json", serde_json::to_string_pretty(entries).map_err(std::io::Error::from)? + "\n"),
+    };
+
+    let path = root.as_ref().join(name);
+    OutputWriter::new().set_mode(WriteMode::Overwrite).write(&path, &contents, &Metadata::new())
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
@@ -0,0 +1,149 @@
+//! The languages that have a corpus bundled with this crate.  Each corpus is compiled into the crate, so generation works from any working directory.
+
+use serde::Serialize;
+use std::{fmt::Display, str::FromStr};
+
+/// A language with a bundled corpus, named by its ISO 639-1 code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Arabic
+    Ar,
+
+    /// Czech
+    Cs,
+
+    /// German
+    De,
+
+    /// English
+    En,
+
+    /// Spanish
+    Es,
+
+    /// French
+    Fr,
+
+    /// Irish
+    Ga,
+
+    /// Hindi
+    Hi,
+
+    /// Japanese
+    Ja,
+
+    /// Latin
+    La,
+
+    /// Russian
+    Ru,
+
+    /// Swedish
+    Sv,
+}
+
+impl Language {
+    /// Every language with a bundled corpus.
+    pub const ALL: [Language; 12] = [
+        Language::Ar,
+        Language::Cs,
+        Language::De,
+        Language::En,
+        Language::Es,
+        Language::Fr,
+        Language::Ga,
+        Language::Hi,
+        Language::Ja,
+        Language::La,
+        Language::Ru,
+        Language::Sv,
+    ];
+
+    /// The ISO 639-1 code of this language, e.g. "en".
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Ar => "ar",
+            Language::Cs => "cs",
+            Language::De => "de",
+            Language::En => "en",
+            Language::Es => "es",
+            Language::Fr => "fr",
+            Language::Ga => "ga",
+            Language::Hi => "hi",
+            Language::Ja => "ja",
+            Language::La => "la",
+            Language::Ru => "ru",
+            Language::Sv => "sv",
+        }
+    }
+
+    /// The English name of this language, which is also the name of its file in `corpus/`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Ar => "arabic",
+            Language::Cs => "czech",
+            Language::De => "german",
+            Language::En => "english",
+            Language::Es => "spanish",
+            Language::Fr => "french",
+            Language::Ga => "irish",
+            Language::Hi => "hindi",
+            Language::Ja => "japanese",
+            Language::La => "latin",
+            Language::Ru => "russian",
+            Language::Sv => "swedish",
+        }
+    }
+
+    /// The bundled word list for this language, one word per line.
+    pub fn corpus(&self) -> &'static str {
+        match self {
+            Language::Ar => include_str!("../../corpus/arabic.txt"),
+            Language::Cs => include_str!("../../corpus/czech.txt"),
+            Language::De => include_str!("../../corpus/german.txt"),
+            Language::En => include_str!("../../corpus/english.txt"),
+            Language::Es => include_str!("../../corpus/spanish.txt"),
+            Language::Fr => include_str!("../../corpus/french.txt"),
+            Language::Ga => include_str!("../../corpus/irish.txt"),
+            Language::Hi => include_str!("../../corpus/hindi.txt"),
+            Language::Ja => include_str!("../../corpus/japanese.txt"),
+            Language::La => include_str!("../../corpus/latin.txt"),
+            Language::Ru => include_str!("../../corpus/russian.txt"),
+            Language::Sv => include_str!("../../corpus/swedish.txt"),
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Returned when a string is not the ISO 639-1 code of a bundled language.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownLanguage(pub String);
+
+impl Display for UnknownLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown language code: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLanguage {}
+
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    /// Parse an ISO 639-1 code.  Case is ignored, so "EN" and "en" both give English.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.to_ascii_lowercase();
+
+        Language::ALL
+            .into_iter()
+            .find(|l| l.code() == code)
+            .ok_or_else(|| UnknownLanguage(s.to_owned()))
+    }
+}
@@ -0,0 +1,116 @@
+//! Length targets for generated text.  Text can be sized in words, characters, grapheme clusters or bytes, and truncation can be kept to word or sentence boundaries.
+
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The length a paragraph should have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum LengthTarget {
+    /// Exactly this many words
+    Words(usize),
+
+    /// Exactly this many characters (Unicode scalar values).  To meet the count, a cut may split a grapheme cluster, such as a Hindi conjunct; use `Graphemes` to keep them whole.
+    Chars(usize),
+
+    /// Exactly this many grapheme clusters, i.e. user-perceived characters
+    Graphemes(usize),
+
+    /// At most this many bytes of UTF-8
+    Bytes(usize),
+}
+
+/// Where generated text may be cut to meet a length target.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub enum Boundary {
+    /// Cut at any grapheme cluster, or any character for a `Chars` target, so the length is met exactly.
+    #[default]
+    None,
+
+    /// Cut between words, so the text may come out shorter than the target.  Scripts written without spaces can be cut between any two grapheme clusters.
+    Word,
+
+    /// Cut between sentences, so the text may come out shorter than the target.  If not even one sentence fits, the text is cut between words instead.
+    Sentence,
+}
+
+/// Appended to truncated text when an ellipsis is requested.  It counts towards the length target.
+pub const ELLIPSIS: &str = "…";
+
+impl LengthTarget {
+    /// The size of a piece of text in the units of this target.  Words are counted as runs of text between separators.
+    pub(crate) fn measure(&self, text: &str) -> usize {
+        match self {
+            LengthTarget::Words(_) => text.split_whitespace().count(),
+            LengthTarget::Chars(_) => text.chars().count(),
+            LengthTarget::Graphemes(_) => text.graphemes(true).count(),
+            LengthTarget::Bytes(_) => text.len(),
+        }
+    }
+
+    /// The number of units asked for.
+    pub(crate) fn limit(&self) -> usize {
+        match self {
+            LengthTarget::Words(n) | LengthTarget::Chars(n) | LengthTarget::Graphemes(n) | LengthTarget::Bytes(n) => *n,
+        }
+    }
+}
+
+/// Cut text down to a character, grapheme or byte target.  Cuts never split a grapheme cluster, except to meet a character target exactly.
+///
+/// `sentence_ends` holds the byte offset just past each sentence, and `word_separator` is the string between words.
+pub(crate) fn truncate(text: &str, target: LengthTarget, boundary: Boundary, ellipsis: bool, sentence_ends: &[usize], word_separator: &str) -> String {
+    let limit = target.limit();
+    if target.measure(text) <= limit {
+        return text.to_owned();
+    }
+
+    let budget = match ellipsis {
+        true => limit.saturating_sub(target.measure(ELLIPSIS)),
+        false => limit,
+    };
+
+    // the longest prefix that fits the budget, aligned to characters for a character target and to grapheme clusters otherwise
+    let end = match target {
+        LengthTarget::Chars(_) => text.char_indices().nth(budget).map_or(text.len(), |(i, _)| i),
+        _ => {
+            let mut end = 0;
+            let mut used = 0;
+            for (i, g) in text.grapheme_indices(true) {
+                let size = target.measure(g);
+                if used + size > budget {
+                    break;
+                }
+                used += size;
+                end = i + g.len();
+            }
+            end
+        },
+    };
+
+    let word_end = || {
+        if word_separator.is_empty() || text[end..].starts_with(word_separator) {
+            end
+        } else {
+            text[..end].rfind(word_separator).unwrap_or(0)
+        }
+    };
+
+    let cut = match boundary {
+        Boundary::None => end,
+        Boundary::Word => word_end(),
+        Boundary::Sentence => match sentence_ends.iter().rev().find(|e| **e <= end) {
+            Some(e) => *e,
+            None => word_end(),
+        },
+    };
+
+    let mut truncated = match boundary {
+        Boundary::None => text[..cut].to_owned(),
+        _ => text[..cut].trim_end().to_owned(),
+    };
+    if ellipsis && limit >= target.measure(ELLIPSIS) {
+        truncated.push_str(ELLIPSIS);
+    }
+
+    truncated
+}
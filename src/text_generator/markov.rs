@@ -0,0 +1,102 @@
+//! An order-N Markov chain over words.  The chain is trained on running text, so the sentences it produces follow the rhythm of real language instead of drawing every word at random.
+
+use rand::Rng;
+use std::{collections::HashMap, fs};
+
+use super::random_index;
+
+/// Characters that end a sentence in the training text.
+const TERMINATORS: [char; 8] = ['.', '!', '?', '。', '！', '？', '।', '؟'];
+
+/// Upper bound on the length of a generated sentence, in case the chain never reaches an end-of-sentence state.
+const MAX_SENTENCE_WORDS: usize = 64;
+
+/// A state is the last `order` words of a sentence.  `None` pads the state at the start of a sentence.
+type State = Vec<Option<String>>;
+
+/// Word transitions learned from running text.
+pub struct MarkovChain {
+    /// Number of preceding words that determine the next word
+    order: usize,
+
+    /// Every word seen after a given state.  `None` marks the end of a sentence.
+    transitions: HashMap<State, Vec<Option<String>>>,
+}
+
+impl MarkovChain {
+    /// Train a chain of the given order on running text.  The text is split into sentences on common terminators and into words on whitespace.  An order of 0 is treated as 1.
+    pub fn train(text: &str, order: usize) -> MarkovChain {
+        let order = order.max(1);
+        let mut transitions: HashMap<State, Vec<Option<String>>> = HashMap::new();
+
+        for sentence in text.split(&TERMINATORS[..]) {
+            let words: Vec<&str> = sentence.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+
+            let mut state: State = vec![None; order];
+            for word in words {
+                transitions.entry(state.clone()).or_default().push(Some(word.to_owned()));
+                state.remove(0);
+                state.push(Some(word.to_owned()));
+            }
+            transitions.entry(state).or_default().push(None);
+        }
+
+        MarkovChain { order, transitions }
+    }
+
+    /// Read running text from a file and train a chain on it.
+    pub fn from_file(path: &str, order: usize) -> Result<MarkovChain, std::io::Error> {
+        let text = fs::read_to_string(path)?;
+        Ok(MarkovChain::train(&text, order))
+    }
+
+    /// The number of preceding words that determine the next word.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// True if the training text held no sentences.
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    /// Every word the chain can produce, sorted.
+    pub fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = self.transitions
+            .values()
+            .flatten()
+            .flatten()
+            .cloned()
+            .collect();
+        words.sort();
+        words.dedup();
+        words
+    }
+
+    /// Walk the chain from the start-of-sentence state until it reaches an end-of-sentence state.
+    pub fn generate_sentence<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut state: State = vec![None; self.order];
+
+        while words.len() < MAX_SENTENCE_WORDS {
+            let next = match self.transitions.get(&state) {
+                Some(n) => &n[random_index(rng, n.len())],
+                None => break,
+            };
+
+            match next {
+                Some(word) => {
+                    words.push(word.to_owned());
+                    state.remove(0);
+                    state.push(Some(word.to_owned()));
+                },
+                None => break,
+            }
+        }
+
+        words
+    }
+}
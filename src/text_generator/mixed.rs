@@ -0,0 +1,242 @@
+//! Code-switching text that mixes several languages within a paragraph, as in Hindi–English or Spanish–English user content.  Each run of words is tagged with the language it came from, so the output doubles as labelled data for language identification.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::casing;
+use super::error::GeneratorError;
+use super::language::Language;
+use super::sampling::Sampling;
+use super::typography::{Casing, Typography};
+use super::{load_source, random_index, seeded_rng, Corpus, Source};
+
+/// How often the language may change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Granularity {
+    /// Any word may come from another language.
+    Word,
+
+    /// Runs of one to three words switch language together.
+    #[default]
+    Phrase,
+
+    /// Every sentence is in a single language.
+    Sentence,
+}
+
+/// Options that shape mixed-language text.
+#[derive(Clone, Debug, Default)]
+pub struct MixedOptions {
+    /// Where the language may switch
+    granularity: Granularity,
+
+    /// Lower bound on the number of sentences.  Up to three more sentences may be generated.
+    min_sentences: Option<usize>,
+
+    /// How words are drawn from each word list
+    sampling: Sampling,
+}
+
+impl MixedOptions {
+    /// Options that switch language at phrase boundaries and generate 3 to 6 sentences.
+    pub fn new() -> MixedOptions {
+        MixedOptions::default()
+    }
+
+    /// Set where the language may switch.
+    pub fn set_granularity(mut self, granularity: Granularity) -> MixedOptions {
+        self.granularity = granularity;
+        self
+    }
+
+    /// Set the minimum number of sentences.
+    pub fn set_min_sentences(mut self, n: usize) -> MixedOptions {
+        self.min_sentences = Some(n);
+        self
+    }
+
+    /// Set how words are drawn from each word list.
+    pub fn set_sampling(mut self, sampling: Sampling) -> MixedOptions {
+        self.sampling = sampling;
+        self
+    }
+}
+
+/// The corpora to mix, each with a weight and a language tag.
+#[derive(Default)]
+pub struct Mix<'a> {
+    parts: Vec<(Corpus<'a>, Option<Language>, f64)>,
+}
+
+impl<'a> Mix<'a> {
+    /// A mix with no corpora yet.
+    pub fn new() -> Mix<'a> {
+        Mix::default()
+    }
+
+    /// Add a corpus, tagged with its own language.  Weights are relative, so 3 and 1 give a 75/25 split.
+    pub fn add(mut self, corpus: Corpus<'a>, weight: f64) -> Mix<'a> {
+        let language = corpus.language();
+        self.parts.push((corpus, language, weight));
+        self
+    }
+
+    /// Add a corpus and tag it with a language, e.g. a Hindi word list read from a file.  Its words also follow the typography of that language.
+    pub fn add_tagged(mut self, corpus: Corpus<'a>, language: Language, weight: f64) -> Mix<'a> {
+        self.parts.push((corpus, Some(language), weight));
+        self
+    }
+}
+
+/// A run of text from one corpus, as byte offsets into the generated text.  Spans cover words and the separators between words of the same language; sentence punctuation and the space at a switch point belong to no span.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The language tag of the corpus, or `None` if it has no tag
+    pub language: Option<Language>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Generated mixed-language text and the language of each span.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MixedText {
+    pub text: String,
+    pub spans: Vec<Span>,
+}
+
+impl MixedText {
+    /// Each span's text with its language tag.
+    pub fn tagged(&self) -> impl Iterator<Item = (&str, Option<Language>)> {
+        self.spans.iter().map(|s| (&self.text[s.start..s.end], s.language))
+    }
+}
+
+/// A loaded corpus of the mix, with words drawn but not yet used.
+struct Part {
+    source: Source,
+    language: Option<Language>,
+    typography: Typography,
+    pending: Vec<String>,
+}
+
+impl Part {
+    /// Take up to `n` consecutive words, so phrases from a Markov chain keep their word order.
+    fn take<R: Rng + ?Sized>(&mut self, n: usize, rng: &mut R) -> Vec<String> {
+        while self.pending.len() < n {
+            let words = self.source.sentence_words(rng);
+            if words.is_empty() {
+                break;
+            }
+            self.pending.extend(words);
+        }
+
+        let n = n.min(self.pending.len());
+        self.pending.drain(..n).collect()
+    }
+}
+
+/// Generate a paragraph that switches between the corpora of a mix, drawing all randomness from the caller's random number generator.
+/// Each sentence has a base language chosen by weight; at word and phrase granularity, words are then swapped for words of other languages, again by weight.
+pub fn generate_mixed<R: Rng + ?Sized>(mix: Mix, options: &MixedOptions, rng: &mut R) -> Result<MixedText, GeneratorError> {
+    if mix.parts.is_empty() {
+        return Err(GeneratorError::InvalidRange(String::from("a mix needs at least one corpus")));
+    }
+    if let Some(n) = options.min_sentences {
+        if n.checked_add(3).is_none() {
+            return Err(GeneratorError::InvalidRange(format!("min_sentences of {} is too large", n)));
+        }
+    }
+
+    let weights: Vec<f64> = mix.parts.iter().map(|(_, _, w)| *w).collect();
+    let chooser = match WeightedIndex::new(&weights) {
+        Ok(c) if weights.iter().all(|w| w.is_finite()) => c,
+        _ => return Err(GeneratorError::InvalidRange(String::from("mix weights must be finite, not negative, and not all zero")))
+    };
+
+    let mut parts = Vec::new();
+    for (corpus, language, _) in mix.parts {
+        let source = load_source(corpus, &options.sampling, None)?;
+        let typography = language.map_or_else(Typography::latin, |l| l.typography());
+        parts.push(Part { source, language, typography, pending: Vec::new() });
+    }
+
+    let n_sentences = options.min_sentences.unwrap_or(3) + random_index(rng, 4);
+    let mut mixed = MixedText { text: String::new(), spans: Vec::new() };
+    let mut previous_base: Option<usize> = None;
+    for _ in 0..n_sentences {
+        let base = chooser.sample(rng);
+        let words = sentence_words(&mut parts, base, &chooser, options.granularity, rng);
+
+        if let Some(p) = previous_base {
+            mixed.text.push_str(separator(parts[p].typography.sentence_separator, parts[base].typography.sentence_separator));
+        }
+        write_sentence(&mut mixed, &parts, base, words);
+        previous_base = Some(base);
+    }
+
+    Ok(mixed)
+}
+
+/// Generate mixed-language text from a seed.  The same seed, mix and options always produce the same text and spans.
+pub fn generate_mixed_from_seed(mix: Mix, options: &MixedOptions, seed: u64) -> Result<MixedText, GeneratorError> {
+    generate_mixed(mix, options, &mut seeded_rng(seed))
+}
+
+/// The words of one sentence, each with the index of the part it came from.
+fn sentence_words<R: Rng + ?Sized>(parts: &mut [Part], base: usize, chooser: &WeightedIndex<f64>, granularity: Granularity, rng: &mut R) -> Vec<(usize, String)> {
+    let mut words: Vec<(usize, String)> = parts[base].source.sentence_words(rng).into_iter().map(|w| (base, w)).collect();
+
+    let run_length = match granularity {
+        Granularity::Sentence => return words,
+        Granularity::Word => 1,
+        Granularity::Phrase => 3,
+    };
+
+    let mut i = 0;
+    while i < words.len() {
+        let end = (i + 1 + random_index(rng, run_length)).min(words.len());
+        let part = chooser.sample(rng);
+        if part != base {
+            for (slot, word) in words[i..end].iter_mut().zip(parts[part].take(end - i, rng)) {
+                *slot = (part, word);
+            }
+        }
+        i = end;
+    }
+
+    words
+}
+
+/// Append a cased and punctuated sentence to the text and record its spans.
+fn write_sentence(mixed: &mut MixedText, parts: &[Part], base: usize, words: Vec<(usize, String)>) {
+    let mut previous: Option<usize> = None;
+
+    for (part, mut word) in words {
+        let typography = &parts[part].typography;
+        if typography.casing == Casing::Sentence {
+            word = casing::to_lower(&word, typography.case_locale);
+        }
+        if previous.is_none() && typography.casing != Casing::None {
+            word = casing::capitalize_first(&word, typography.case_locale);
+        }
+
+        if let Some(p) = previous {
+            mixed.text.push_str(separator(parts[p].typography.word_separator, typography.word_separator));
+        }
+        let start = mixed.text.len();
+        mixed.text.push_str(&word);
+
+        match mixed.spans.last_mut() {
+            Some(span) if previous == Some(part) => span.end = mixed.text.len(),
+            _ => mixed.spans.push(Span { language: parts[part].language, start, end: mixed.text.len() })
+        }
+        previous = Some(part);
+    }
+
+    mixed.text.push_str(parts[base].typography.full_stop);
+}
+
+/// The separator between text in two scripts: their shared separator, or a space where they differ.
+fn separator<'s>(before: &'s str, after: &'s str) -> &'s str {
+    if before == after { before } else { " " }
+}
@@ -1,118 +1,528 @@
-use rand::{Rng, thread_rng};
-use std::{fs, error::Error, io::{Write}, time::{SystemTime, UNIX_EPOCH}};
+pub mod builder;
+pub mod document;
+pub mod error;
+pub mod fixtures;
+pub mod language;
+pub mod length;
+pub mod markov;
+pub mod mixed;
+pub mod output;
+pub mod punctuation;
+pub mod readability;
+pub mod resources;
+pub mod sampling;
+pub mod stats;
+pub mod stream;
+pub mod template;
+pub mod torture;
+pub mod typography;
+pub mod vocabulary;
 
+use rand::{distributions::{Distribution, WeightedIndex}, Rng, SeedableRng, thread_rng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::{fs, error::Error, io::Read, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::casing;
+use self::error::GeneratorError;
+use self::language::Language;
+use self::length::{Boundary, LengthTarget};
+use self::markov::MarkovChain;
+use self::output::{Metadata, OutputWriter, WriteMode};
+use self::punctuation::Punctuation;
+use self::readability::{ReadingLevel, ReadingTarget};
+use self::sampling::Sampling;
+use self::typography::{Casing, Typography};
+use self::vocabulary::Vocabulary;
+
+/// Where a generator reads its words from.  Word lists hold one word per line.
 pub enum Corpus<'a> {
+    /// A word list file
     FromFile(&'a str), 
+
+    /// Words already in memory, e.g. a vocabulary fetched from a database
+    FromVec(Vec<String>), 
+
+    /// Borrowed words, e.g. a vocabulary held in config
+    FromSlice(&'a [&'a str]), 
+
+    /// A word list read to the end from any reader, such as a socket or a decompressor
+    FromReader(Box<dyn Read + 'a>), 
+
+    /// Every file in a directory, read as word lists in file name order.  Subdirectories are skipped.
+    FromDir(&'a str), 
+
+    /// The word list bundled with the crate for a language
+    Embedded(Language), 
+
+    /// Sentences come from an order-N Markov chain trained on the running text at `training`.  
+    /// If the training text cannot be read or holds no sentences, words are drawn from the word list at `fallback` instead.
+    Markov { training: &'a str, order: usize, fallback: &'a str }, 
+
+    /// A corpus already read by `Corpus::load`.  Generating from it does no I/O.
+    Loaded(Vocabulary), 
+}
+
+impl<'a> Corpus<'a> {
+    /// The language of this corpus, if it is one of the bundled corpora.
+    pub fn language(&self) -> Option<Language> {
+        match self {
+            Corpus::Embedded(language) => Some(*language), 
+            Corpus::Loaded(vocabulary) => vocabulary.language(), 
+            _ => None
+        }
+    }
+
+    /// Read and preprocess the corpus once.  Lines are trimmed, blank lines are dropped, repeated words are kept once and words are indexed by length.  
+    /// The result can be cloned cheaply and shared across threads; pass it back as `Corpus::Loaded` to generate without rereading the corpus.
+    pub fn load(self) -> Result<Vocabulary, GeneratorError> {
+        if let Corpus::Embedded(language) = self {
+            return Ok(Vocabulary::embedded(language));
+        }
+
+        match self.read()? {
+            Contents::Lines(lines, language) => Vocabulary::from_lines(lines, language), 
+            Contents::Vocabulary(vocabulary) => Ok(vocabulary), 
+        }
+    }
+
+    /// Read the corpus without preprocessing it.  Word lists come back as raw lines; trained chains and loaded corpora are already vocabularies.
+    pub(crate) fn read(self) -> Result<Contents, GeneratorError> {
+        let lines = match self {
+            Corpus::FromFile(f) => {
+                read_corpus_from_file(f)?
+            }, 
+            Corpus::FromVec(words) => {
+                words
+            }, 
+            Corpus::FromSlice(words) => {
+                words.iter().map(|w| w.to_string()).collect()
+            }, 
+            Corpus::FromReader(mut reader) => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                split_corpus(&text)
+            }, 
+            Corpus::FromDir(d) => {
+                read_corpus_from_dir(d)?
+            }, 
+            Corpus::Embedded(language) => {
+                return Ok(Contents::Lines(split_corpus(language.corpus()), Some(language)));
+            }, 
+            Corpus::Markov { training, order, fallback } => {
+                match MarkovChain::from_file(training, order) {
+                    Ok(chain) if !chain.is_empty() => return Ok(Contents::Vocabulary(Vocabulary::from_chain(chain))), 
+                    _ => read_corpus_from_file(fallback)?
+                }
+            }, 
+            Corpus::Loaded(vocabulary) => {
+                return Ok(Contents::Vocabulary(vocabulary));
+            }, 
+        };
+
+        Ok(Contents::Lines(lines, None))
+    }
+}
+
+/// A corpus as read, before preprocessing.
+pub(crate) enum Contents {
+    /// The lines of a word list, and its language if it is bundled
+    Lines(Vec<String>, Option<Language>), 
+
+    /// Words that have already been preprocessed
+    Vocabulary(Vocabulary), 
+}
+
+/// Where the words of each sentence come from once a Corpus has been loaded.
+struct Source {
+    vocabulary: Vocabulary, 
+
+    /// The distribution words are drawn from, unless sampling is uniform
+    weights: Option<WeightedIndex<u64>>, 
+
+    /// Sentence length and word choice for a reading level, if one is set
+    reading: Option<ReadingTarget>, 
+}
+
+/// The random number generator used for seeded generation.  
+/// ChaCha8 produces the same stream for a given seed on every platform, so a seed, corpus and set of options always give byte-identical text.
+pub type SeededRng = ChaCha8Rng;
+
+/// Create a portable random number generator from a seed.
+pub fn seeded_rng(seed: u64) -> SeededRng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+/// Pick an index below `len`.  
+/// Ranges over usize draw a different number of bits on 32-bit and 64-bit targets, so sampling goes through u64 to keep seeded output portable.
+pub(crate) fn random_index<R: Rng + ?Sized>(rng: &mut R, len: usize) -> usize {
+    rng.gen_range(0..len as u64) as usize
+}
+
+/// Clamp a probability to between 0 and 1.  NaN and infinite values become 0, since `gen_bool` panics on NaN.
+pub(crate) fn probability(p: f64) -> f64 {
+    if !p.is_finite() {
+        return 0.0;
+    }
+    p.clamp(0.0, 1.0)
+}
+
+/// Options that shape a generated paragraph.  Every option is unset by default.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ParagraphOptions {
+    /// Lower bound on the number of sentences.  Up to three more sentences may be generated.  Ignored when a length target is set.
+    min_sentences: Option<usize>, 
+
+    /// The length of the paragraph in words, characters, grapheme clusters or bytes
+    length: Option<LengthTarget>, 
+
+    /// Where the paragraph may be cut to meet its length target
+    boundary: Boundary, 
+
+    /// Whether truncated text ends with an ellipsis
+    ellipsis: bool, 
+
+    /// How words are drawn from a word list
+    sampling: Sampling, 
+
+    /// Separators, punctuation and casing.  Defaults to the corpus language, or Latin conventions if the corpus has no language.
+    typography: Option<Typography>, 
+
+    /// How often sentences become questions or exclamations and gain commas, semicolons, asides and quotations.  Every sentence is a plain statement when unset.
+    punctuation: Option<Punctuation>, 
+
+    /// The readability band that sentence length and word choice aim for.  Sentences follow the corpus alone when unset.
+    reading_level: Option<ReadingLevel>, 
+}
+
+impl ParagraphOptions {
+    /// Options that generate 3 to 6 sentences with no size limit.
+    pub fn new() -> ParagraphOptions {
+        ParagraphOptions::default()
+    }
+
+    /// Set the minimum number of sentences.
+    pub fn set_min_sentences(mut self, n: usize) -> ParagraphOptions {
+        self.min_sentences = Some(n);
+        self
+    }
+
+    /// Set the maximum number of bytes.  Same as `set_length(LengthTarget::Bytes(n))`.
+    pub fn set_max_bytes(mut self, n: usize) -> ParagraphOptions {
+        self.length = Some(LengthTarget::Bytes(n));
+        self
+    }
+
+    /// Set the length of the paragraph.  Sentences are generated until the target is reached, then the text is cut to fit.
+    pub fn set_length(mut self, length: LengthTarget) -> ParagraphOptions {
+        self.length = Some(length);
+        self
+    }
+
+    /// Set where the paragraph may be cut to meet its length target.
+    pub fn set_boundary(mut self, boundary: Boundary) -> ParagraphOptions {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Turn the ellipsis at the end of truncated text on or off.
+    pub fn set_ellipsis(mut self, ellipsis: bool) -> ParagraphOptions {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Set the typographic conventions, e.g. `Language::Ja.typography()` for a Japanese word list read from a file.
+    pub fn set_typography(mut self, typography: Typography) -> ParagraphOptions {
+        self.typography = Some(typography);
+        self
+    }
+
+    /// Set how often each kind of punctuation appears, e.g. `Punctuation::new()` for a mix similar to ordinary prose.
+    pub fn set_punctuation(mut self, punctuation: Punctuation) -> ParagraphOptions {
+        self.punctuation = Some(punctuation);
+        self
+    }
+
+    /// Set how words are drawn from a word list.
+    pub fn set_sampling(mut self, sampling: Sampling) -> ParagraphOptions {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Aim for a readability band, e.g. `ReadingLevel::FleschKincaidGrade { min: 6.0, max: 8.0 }`.  Sentence length follows the band, and each word is the best fit of several drawn from the corpus, so short words make easy text and long ones hard text.  Markov corpora keep their own sentences.
+    pub fn set_reading_level(mut self, level: ReadingLevel) -> ParagraphOptions {
+        self.reading_level = Some(level);
+        self
+    }
+
+    /// Check that the options are within the ranges the generator can handle.
+    fn validate(&self) -> Result<(), GeneratorError> {
+        if let Some(level) = &self.reading_level {
+            level.validate()?;
+        }
+        match self.min_sentences {
+            Some(n) if n.checked_add(3).is_none() => Err(GeneratorError::InvalidRange(format!("min_sentences of {} is too large", n))), 
+            _ => Ok(())
+        }
+    }
+
+    /// The typography to use for a corpus in the given language.
+    fn typography_for(&self, language: Option<Language>) -> Typography {
+        match (&self.typography, language) {
+            (Some(t), _) => t.clone(), 
+            (None, Some(l)) => l.typography(), 
+            (None, None) => Typography::latin()
+        }
+    }
 }
 
-pub fn generate_text_for_language(language: i32, write_to_file: bool) {
+/// Generate a paragraph from the bundled corpus of a language, print it, and optionally write it to a file named after the current time.
+pub fn generate_text_for_language(language: Language, write_to_file: bool) -> Result<(), GeneratorError> {
+    let corpus = Corpus::Embedded(language);
     let paragraph = match language {
-        0 => generate_paragraph(Corpus::FromFile("english.txt"), Some(100), Some(1000)),
-        1 => generate_paragraph(Corpus::FromFile("french.txt"), None, None),
-        2 => generate_paragraph(Corpus::FromFile("spanish.txt"), None, Some(500)),
-        3 => generate_paragraph(Corpus::FromFile("hindi.txt"), Some(50), None),
-        4 => generate_paragraph(Corpus::FromFile("russian.txt"), Some(50), None),
-        5 => generate_paragraph(Corpus::FromFile("arabic.txt"), Some(50), None),
-        6 => generate_paragraph(Corpus::FromFile("japanese.txt"), Some(50), None),
-        7 => generate_paragraph(Corpus::FromFile("german.txt"), Some(50), None),
-        10 => generate_paragraph(Corpus::FromFile("irish.txt"), Some(50), None),
-        11 => generate_paragraph(Corpus::FromFile("swedish.txt"), Some(50), None),
-        _ => panic!("Invalid language index"),
+        Language::En => try_generate_paragraph(corpus, Some(100), Some(1000))?,
+        Language::Fr => try_generate_paragraph(corpus, None, None)?,
+        Language::Es => try_generate_paragraph(corpus, None, Some(500))?,
+        _ => try_generate_paragraph(corpus, Some(50), None)?,
     };
     println!("{}", paragraph);
     
-    if write_to_file == true{
-        let res = write_paragraph_to_file(paragraph, None);
-        match res {
-            Ok(_) => println!("File created successfully."), 
-            Err(e) => panic!("{}", e.to_string())
-        };
+    if write_to_file {
+        write_paragraph_to_file(paragraph, None).map_err(|e| match e.downcast::<std::io::Error>() {
+            Ok(io) => GeneratorError::Io(*io), 
+            Err(other) => GeneratorError::Io(std::io::Error::other(other.to_string()))
+        })?;
+        println!("File created successfully.");
     }
+
+    Ok(())
+}
+
+/// Load a Corpus and prepare to draw words from it.  Every kind of Corpus is loaded through here.
+fn load_source(corpus: Corpus, sampling: &Sampling, reading_level: Option<&ReadingLevel>) -> Result<Source, GeneratorError> {
+    let vocabulary = corpus.load()?;
+    let weights = match vocabulary.chain() {
+        Some(_) => None, 
+        None => sampling::weighted_index(sampling, vocabulary.len(), vocabulary.counts())?
+    };
+
+    Ok(Source { vocabulary, weights, reading: reading_level.map(|l| l.target()) })
 }
 
-fn read_corpus_from_file(path: &str) -> Vec<String> {
-    fs::read_to_string(path)
-        .expect("Failed to parse file.")
-        .split("\n")
+fn read_corpus_from_file(path: &str) -> Result<Vec<String>, GeneratorError> {
+    let text = fs::read_to_string(path).map_err(|e| missing_corpus(e, path))?;
+    Ok(split_corpus(&text))
+}
+
+fn read_corpus_from_dir(path: &str) -> Result<Vec<String>, GeneratorError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path).map_err(|e| missing_corpus(e, path))? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+
+    let mut words = Vec::new();
+    for file in files {
+        words.extend(split_corpus(&fs::read_to_string(file)?));
+    }
+
+    Ok(words)
+}
+
+/// Report a corpus path that does not exist as missing, and any other failure as an I/O error.
+fn missing_corpus(e: std::io::Error, path: &str) -> GeneratorError {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => GeneratorError::MissingCorpus(path.to_owned()), 
+        _ => GeneratorError::Io(e)
+    }
+}
+
+fn split_corpus(text: &str) -> Vec<String> {
+    text.split("\n")
         .map(|s| s.to_owned())
         .collect::<Vec<String>>()
 }
 
+/// Write a paragraph as plain text.  An explicit path must not exist yet; without one the file is named after the current time, with a number added if that name is taken.  Use [output::OutputWriter] for other formats and modes.
 pub fn write_paragraph_to_file(paragraph: String, path: Option<String>) -> Result<bool, Box<dyn Error>> {
-    let file_name = match path {
-        Some(x) => x, 
+    let (file_name, mode) = match path {
+        Some(x) => (x, WriteMode::CreateNew), 
         None => {
             let time = SystemTime::now().duration_since(UNIX_EPOCH)?;
-            let path = format!("{}.txt", time.as_secs());
-            path
+            (format!("{}.txt", time.as_secs()), WriteMode::UniqueSuffix)
         }
     };
 
-    let mut file = fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(file_name)?;
-    file.write_all(paragraph.as_bytes())?;
+    OutputWriter::new()
+        .set_mode(mode)
+        .write(file_name, &paragraph, &Metadata::new())?;
 
     Ok(true)
 }
 
+/// Generate a paragraph with a fresh random seed.  Panics if the corpus cannot be read or holds no words; use [try_generate_paragraph] to handle those cases.
 pub fn generate_paragraph(corpus: Corpus, min_sentences: Option<usize>, max_bytes: Option<usize>) -> String {
-    let mut rng = thread_rng();
+    try_generate_paragraph(corpus, min_sentences, max_bytes).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    let word_list: Vec<String> = match corpus {
-        Corpus::FromFile(f) => {
-            read_corpus_from_file(f)
-        }, 
+/// Generate a paragraph with a fresh random seed, or an error if the corpus is missing or empty or the options are out of range.
+pub fn try_generate_paragraph(corpus: Corpus, min_sentences: Option<usize>, max_bytes: Option<usize>) -> Result<String, GeneratorError> {
+    let options = ParagraphOptions {
+        min_sentences, 
+        length: max_bytes.map(LengthTarget::Bytes), 
+        ..ParagraphOptions::default()
     };
+    generate_paragraph_with_rng(corpus, &options, &mut thread_rng())
+}
 
-    let n_sentences;
-    if let Some(n) = min_sentences {
-        n_sentences = rng.gen_range(n..n+4);
-    } else {
-        n_sentences = rng.gen_range(3..7);
+/// Join words into a sentence using the separators, casing and full stop of a script.
+fn assemble_sentence(words: &[String], typography: &Typography) -> String {
+    let mut sentence = join_words(words, typography);
+    sentence.push_str(typography.full_stop);
+    sentence
+}
+
+/// Join and case words as the start of a sentence, without its full stop.
+fn join_words(words: &[String], typography: &Typography) -> String {
+    let mut sentence = words.join(typography.word_separator);
+    apply_casing(&mut sentence, typography);
+    sentence
+}
+
+/// Case a sentence as its script requires.
+fn apply_casing(sentence: &mut String, typography: &Typography) {
+    match typography.casing {
+        Casing::Sentence => *sentence = casing::to_sentence(sentence, typography.case_locale), 
+        Casing::CapitalizeFirst => *sentence = casing::capitalize_first(sentence, typography.case_locale), 
+        Casing::None => ()
+    }
+}
+
+/// Turn words into a sentence, punctuated as the options ask.  Without `terminate` the sentence has no closing mark, for text that is cut short.
+fn make_sentence<R: Rng + ?Sized>(words: &[String], typography: &Typography, options: &ParagraphOptions, rng: &mut R, terminate: bool) -> String {
+    match (&options.punctuation, terminate) {
+        (Some(p), _) => punctuation::punctuate(words, typography, p, rng, terminate), 
+        (None, true) => assemble_sentence(words, typography), 
+        (None, false) => join_words(words, typography)
     }
+}
 
-    let mut sentences = Vec::new();
-    for _ in 0..n_sentences {
-        let n_words = rng.gen_range(5..12);
+/// Generate a paragraph from a seed.  The same seed, corpus and options produce the same paragraph on every run and every platform.
+pub fn generate_paragraph_from_seed(corpus: Corpus, options: &ParagraphOptions, seed: u64) -> Result<String, GeneratorError> {
+    generate_paragraph_with_rng(corpus, options, &mut seeded_rng(seed))
+}
+
+/// Generate a paragraph drawing all randomness from the caller's random number generator.
+pub fn generate_paragraph_with_rng<R: Rng + ?Sized>(corpus: Corpus, options: &ParagraphOptions, rng: &mut R) -> Result<String, GeneratorError> {
+    options.validate()?;
+    let typography = options.typography_for(corpus.language());
+    let source = load_source(corpus, &options.sampling, options.reading_level.as_ref())?;
+
+    Ok(build_paragraph(&source, &typography, options, rng))
+}
+
+impl Source {
+    /// The words of one sentence, before they are joined and punctuated.
+    fn sentence_words<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<String> {
+        if let Some(chain) = self.vocabulary.chain() {
+            return chain.generate_sentence(rng);
+        }
+
+        let word_list = self.vocabulary.words();
+        if let Some(target) = &self.reading {
+            return target.sentence(word_list, |rng| self.draw(rng), rng);
+        }
+
+        let n_words = 5 + random_index(rng, 7);
         let mut words = Vec::new();
         for _ in 0..n_words {
-            let word_index = rng.gen_range(0..word_list.len());
-            let word = word_list[word_index].as_str();
-            words.push(word);
+            words.push(word_list[self.draw(rng)].to_owned());
         }
+        words
+    }
 
-        let mut sentence = words.join(" ");
-        sentence.make_ascii_lowercase();
-
-        let first_char = sentence.chars().next().unwrap();
-        //first character may not be one byte-aligned
-        let first_char_len = &first_char.len_utf8();
-        sentence.replace_range(..first_char_len, &first_char.to_uppercase().to_string());
-        sentence.push('.');
-        sentences.push(sentence);
+    /// The index of a word, drawn as the sampling asks.
+    fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        match &self.weights {
+            Some(w) => w.sample(rng), 
+            None => random_index(rng, self.vocabulary.len())
+        }
     }
-    
-    let paragraph = sentences.join(" ");
-    
-    match max_bytes {
-        None => paragraph, 
-        Some(m) => {
-            //only read up to limit of bytes requested by the user
-            let mut truncated = String::new();
-            let mut num_bytes: usize = 0;
-            for char in paragraph.chars() {
-                let len = char.len_utf8();
-                if num_bytes + len < m {
-                    truncated.push(char);
-                    num_bytes += len;
-                } else {
-                    break;
-                }
+}
+
+/// Generate one paragraph from a loaded source.
+fn build_paragraph<R: Rng + ?Sized>(source: &Source, typography: &Typography, options: &ParagraphOptions, rng: &mut R) -> String {
+    let target = match options.length {
+        None => {
+            let n_sentences;
+            if let Some(n) = options.min_sentences {
+                n_sentences = n + random_index(rng, 4);
+            } else {
+                n_sentences = 3 + random_index(rng, 4);
             }
 
-            truncated
+            let mut sentences = Vec::new();
+            for _ in 0..n_sentences {
+                let words = source.sentence_words(rng);
+                sentences.push(make_sentence(&words, typography, options, rng, true));
+            }
+
+            return sentences.join(typography.sentence_separator);
+        }, 
+        Some(LengthTarget::Words(n)) => return build_words(source, typography, options, n, rng), 
+        Some(t) => t
+    };
+
+    //generate whole sentences until the target is reached, then cut the text down to it
+    let mut paragraph = String::new();
+    let mut sentence_ends = Vec::new();
+    let mut size = 0;
+    while size < target.limit() {
+        if !paragraph.is_empty() {
+            paragraph.push_str(typography.sentence_separator);
+            size += target.measure(typography.sentence_separator);
         }
+
+        let words = source.sentence_words(rng);
+        let sentence = make_sentence(&words, typography, options, rng, true);
+        size += target.measure(&sentence);
+        paragraph.push_str(&sentence);
+        sentence_ends.push(paragraph.len());
     }
+
+    length::truncate(&paragraph, target, options.boundary, options.ellipsis, &sentence_ends, typography.word_separator)
+}
+
+/// Generate exactly `n` words.  The last sentence is cut short if needed, and keeps its full stop only when cuts must fall on sentence boundaries.
+fn build_words<R: Rng + ?Sized>(source: &Source, typography: &Typography, options: &ParagraphOptions, n: usize, rng: &mut R) -> String {
+    let mut sentences = Vec::new();
+    let mut remaining = n;
+
+    while remaining > 0 {
+        //some corpus entries hold several words, so count the words inside them
+        let mut words: Vec<String> = source.sentence_words(rng)
+            .iter()
+            .flat_map(|w| w.split_whitespace())
+            .map(|w| w.to_owned())
+            .collect();
+        if words.len() > remaining {
+            words.truncate(remaining);
+
+            let terminate = options.boundary == Boundary::Sentence;
+            let mut sentence = make_sentence(&words, typography, options, rng, terminate);
+            if !terminate && options.ellipsis {
+                sentence.push_str(length::ELLIPSIS);
+            }
+            sentences.push(sentence);
+            break;
+        }
+
+        remaining -= words.len();
+        sentences.push(make_sentence(&words, typography, options, rng, true));
+    }
+
+    sentences.join(typography.sentence_separator)
 }
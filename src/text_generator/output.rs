@@ -0,0 +1,241 @@
+//! Writing generated text to files.  Every write goes to a temporary file in the target directory first and is then moved into place, so readers never see a half-written file.  Appends are the exception: the new text is added to the end of the file in a single write.  Text can be written as plain text, as a JSON document carrying metadata, or as one JSON object per line (NDJSON).
+
+use serde::Serialize;
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::error::GeneratorError;
+use super::language::Language;
+use super::ParagraphOptions;
+
+/// What to do when the output file already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub enum WriteMode {
+    /// Fail if the file exists.
+    #[default]
+    CreateNew,
+
+    /// Replace the file.
+    Overwrite,
+
+    /// Add to the end of the file.  Plain text is separated from the existing text by a blank line.
+    Append,
+
+    /// Write to a new file instead, numbering it "name-1.txt", "name-2.txt" and so on.
+    UniqueSuffix,
+}
+
+/// How generated text is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub enum OutputFormat {
+    /// The text as it is.
+    #[default]
+    Text,
+
+    /// A pretty-printed JSON document holding the text and its metadata.
+    Json,
+
+    /// One JSON object per line, holding the text and its metadata.  Appending adds a line.
+    Ndjson,
+}
+
+/// Facts about how a text was generated, written alongside it in the JSON formats.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Metadata {
+    language: Option<Language>,
+    seed: Option<u64>,
+    options: Option<ParagraphOptions>,
+}
+
+impl Metadata {
+    /// Metadata with nothing recorded.
+    pub fn new() -> Metadata {
+        Metadata::default()
+    }
+
+    /// Record the language of the text.
+    pub fn set_language(mut self, language: Language) -> Metadata {
+        self.language = Some(language);
+        self
+    }
+
+    /// Record the seed the text was generated from.
+    pub fn set_seed(mut self, seed: u64) -> Metadata {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Record the options the text was generated with.
+    pub fn set_options(mut self, options: ParagraphOptions) -> Metadata {
+        self.options = Some(options);
+        self
+    }
+}
+
+/// Writes generated text to files.  By default it writes plain text, refuses to replace existing files and creates missing directories.
+#[derive(Clone, Debug)]
+pub struct OutputWriter {
+    mode: WriteMode,
+    format: OutputFormat,
+
+    /// Whether missing parent directories are created
+    create_dirs: bool,
+}
+
+impl Default for OutputWriter {
+    fn default() -> Self {
+        OutputWriter { mode: WriteMode::CreateNew, format: OutputFormat::Text, create_dirs: true }
+    }
+}
+
+/// Distinguishes the temporary files of concurrent writes from one process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl OutputWriter {
+    /// A writer of plain text that creates new files only.
+    pub fn new() -> OutputWriter {
+        OutputWriter::default()
+    }
+
+    /// Set what happens when the file already exists.
+    pub fn set_mode(mut self, mode: WriteMode) -> OutputWriter {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the output format.
+    pub fn set_format(mut self, format: OutputFormat) -> OutputWriter {
+        self.format = format;
+        self
+    }
+
+    /// Turn the creation of missing parent directories on or off.
+    pub fn set_create_dirs(mut self, create_dirs: bool) -> OutputWriter {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// Write text and its metadata to a file and return the path it was written to, which differs from `path` in `UniqueSuffix` mode.
+    pub fn write(&self, path: impl AsRef<Path>, text: &str, metadata: &Metadata) -> Result<PathBuf, GeneratorError> {
+        let path = path.as_ref();
+        if self.mode == WriteMode::Append && self.format == OutputFormat::Json {
+            return Err(GeneratorError::UnsupportedOutput(String::from("a JSON document cannot be appended to; use NDJSON")));
+        }
+
+        let directory = match path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+            _ => PathBuf::from(".")
+        };
+        if self.create_dirs {
+            fs::create_dir_all(&directory)?;
+        }
+
+        let contents = self.render(text, metadata)?;
+        if self.mode == WriteMode::Append {
+            return self.append(path, &contents);
+        }
+
+        let temp = directory.join(format!(
+            ".{}.{}.{}.tmp",
+            path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(),
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temp, contents)?;
+
+        let result = self.move_into_place(&temp, path);
+        let _ = fs::remove_file(&temp);
+        result
+    }
+
+    /// The file contents for one text.
+    fn render(&self, text: &str, metadata: &Metadata) -> Result<String, GeneratorError> {
+        let record = json!({
+            "text": text,
+            "language": metadata.language,
+            "seed": metadata.seed,
+            "options": metadata.options,
+        });
+
+        let contents = match self.format {
+            OutputFormat::Text => text.to_owned(),
+            OutputFormat::Json => serde_json::to_string_pretty(&record).map_err(std::io::Error::from)? + "\n",
+            OutputFormat::Ndjson => serde_json::to_string(&record).map_err(std::io::Error::from)? + "\n",
+        };
+        Ok(contents)
+    }
+
+    /// Add contents to the end of a file, creating it if needed.  Only the new contents are written, so an append costs the same however large the file has grown, concurrent appends do not lose each other's records, and the file keeps its permissions.
+    fn append(&self, path: &Path, contents: &str) -> Result<PathBuf, GeneratorError> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let len = file.metadata()?.len();
+
+        // plain text gets a blank line in between, and NDJSON a line break if the last line lacks one
+        let separator = match self.format {
+            OutputFormat::Text if len > 0 => "\n\n",
+            OutputFormat::Ndjson if len > 0 && last_byte(&mut file)? != b'\n' => "\n",
+            _ => ""
+        };
+        file.write_all(format!("{}{}", separator, contents).as_bytes())?;
+        Ok(path.to_path_buf())
+    }
+
+    /// Move a finished temporary file to its destination.  New files are claimed with `claim`, which fails if the destination exists, so two writers can never claim the same name.
+    fn move_into_place(&self, temp: &Path, path: &Path) -> Result<PathBuf, GeneratorError> {
+        let hard_link = |from: &Path, to: &Path| fs::hard_link(from, to);
+        match self.mode {
+            WriteMode::Overwrite | WriteMode::Append => {
+                fs::rename(temp, path)?;
+                Ok(path.to_path_buf())
+            },
+            WriteMode::CreateNew => {
+                claim(temp, path, hard_link)?;
+                Ok(path.to_path_buf())
+            },
+            WriteMode::UniqueSuffix => {
+                for n in 0.. {
+                    let candidate = if n == 0 { path.to_path_buf() } else { numbered(path, n) };
+                    match claim(temp, &candidate, hard_link) {
+                        Ok(()) => return Ok(candidate),
+                        Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                        Err(e) => return Err(e.into())
+                    }
+                }
+                unreachable!()
+            }
+        }
+    }
+}
+
+/// Give a finished temporary file the name `path`, failing with `AlreadyExists` if the name is taken.  The file is hard-linked into place with `link`.  On filesystems without hard links, such as FAT, exFAT and some network mounts, the name is claimed with `create_new` and the contents are copied instead.
+pub(crate) fn claim(temp: &Path, path: &Path, link: impl Fn(&Path, &Path) -> io::Result<()>) -> io::Result<()> {
+    match link(temp, path) {
+        Err(e) if matches!(e.kind(), ErrorKind::Unsupported | ErrorKind::PermissionDenied) => {
+            let mut claimed = OpenOptions::new().write(true).create_new(true).open(path)?;
+            io::copy(&mut File::open(temp)?, &mut claimed)?;
+            Ok(())
+        },
+        result => result
+    }
+}
+
+/// The last byte of a file that is not empty.
+fn last_byte(file: &mut File) -> io::Result<u8> {
+    let mut byte = [0];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// "dir/name.txt" numbered as "dir/name-n.txt".
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, n)
+    };
+    path.with_file_name(name)
+}
@@ -0,0 +1,151 @@
+//! Varied punctuation and sentence types.  Sentences can become questions or exclamations and gain commas, semicolons, parenthetical asides and quoted speech, all written with the punctuation of their language.
+
+use rand::Rng;
+use serde::Serialize;
+
+use super::{apply_casing, probability};
+use super::typography::Typography;
+
+/// How often each kind of punctuation appears.  Every value is a probability from 0 to 1.  Values outside that range are clamped, and NaN counts as 0.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Punctuation {
+    /// Chance that a sentence is a question
+    question: f64,
+
+    /// Chance that a sentence is an exclamation
+    exclamation: f64,
+
+    /// Chance of a comma between two words
+    comma: f64,
+
+    /// Chance of a semicolon between two words
+    semicolon: f64,
+
+    /// Chance that a sentence holds a parenthetical aside
+    parenthetical: f64,
+
+    /// Chance that a sentence holds quoted speech
+    quotation: f64,
+}
+
+impl Default for Punctuation {
+    fn default() -> Self {
+        Punctuation {
+            question: 0.15,
+            exclamation: 0.05,
+            comma: 0.08,
+            semicolon: 0.01,
+            parenthetical: 0.05,
+            quotation: 0.05,
+        }
+    }
+}
+
+impl Punctuation {
+    /// Probabilities that give a mix of punctuation similar to ordinary prose.
+    pub fn new() -> Punctuation {
+        Punctuation::default()
+    }
+
+    /// Punctuation that never appears, to be switched on one kind at a time.
+    pub fn none() -> Punctuation {
+        Punctuation { question: 0.0, exclamation: 0.0, comma: 0.0, semicolon: 0.0, parenthetical: 0.0, quotation: 0.0 }
+    }
+
+    /// Set the chance that a sentence is a question.
+    pub fn set_question(mut self, p: f64) -> Punctuation {
+        self.question = probability(p);
+        self
+    }
+
+    /// Set the chance that a sentence is an exclamation.
+    pub fn set_exclamation(mut self, p: f64) -> Punctuation {
+        self.exclamation = probability(p);
+        self
+    }
+
+    /// Set the chance of a comma between two words.
+    pub fn set_comma(mut self, p: f64) -> Punctuation {
+        self.comma = probability(p);
+        self
+    }
+
+    /// Set the chance of a semicolon between two words.
+    pub fn set_semicolon(mut self, p: f64) -> Punctuation {
+        self.semicolon = probability(p);
+        self
+    }
+
+    /// Set the chance that a sentence holds a parenthetical aside.
+    pub fn set_parenthetical(mut self, p: f64) -> Punctuation {
+        self.parenthetical = probability(p);
+        self
+    }
+
+    /// Set the chance that a sentence holds quoted speech.
+    pub fn set_quotation(mut self, p: f64) -> Punctuation {
+        self.quotation = probability(p);
+        self
+    }
+}
+
+/// Join words into a punctuated, cased sentence.  Without `terminate` the sentence is left open, for text that is cut short.
+pub(crate) fn punctuate<R: Rng + ?Sized>(words: &[String], typography: &Typography, punctuation: &Punctuation, rng: &mut R, terminate: bool) -> String {
+    let n = words.len();
+    let mut prefixes = vec![String::new(); n];
+    let mut suffixes = vec![String::new(); n];
+
+    // an aside or a quotation wraps a run of words
+    if n >= 5 && rng.gen_bool(punctuation.parenthetical) {
+        let (start, end) = span(rng, 1, n - 1, 2, 3);
+        prefixes[start].push_str(typography.parentheses.0);
+        suffixes[end - 1].push_str(typography.parentheses.1);
+    } else if n >= 2 && rng.gen_bool(punctuation.quotation) {
+        let (start, end) = span(rng, 0, n, 2, 5);
+        prefixes[start].push_str(typography.quotes.0);
+        suffixes[end - 1].push_str(typography.quotes.1);
+    }
+
+    for suffix in suffixes.iter_mut().take(n.saturating_sub(1)) {
+        if rng.gen_bool(punctuation.comma) {
+            suffix.push_str(typography.comma);
+        } else if rng.gen_bool(punctuation.semicolon) {
+            suffix.push_str(typography.punctuation_space);
+            suffix.push_str(typography.semicolon);
+        }
+    }
+
+    let pieces: Vec<String> = words.iter()
+        .enumerate()
+        .map(|(i, w)| format!("{}{}{}", prefixes[i], w, suffixes[i]))
+        .collect();
+    let mut sentence = pieces.join(typography.word_separator);
+    apply_casing(&mut sentence, typography);
+
+    if terminate {
+        let roll: f64 = rng.gen();
+        if roll < punctuation.question {
+            sentence = close(sentence, "¿", typography.question_mark, typography);
+        } else if roll < punctuation.question + punctuation.exclamation {
+            sentence = close(sentence, "¡", typography.exclamation_mark, typography);
+        } else {
+            sentence.push_str(typography.full_stop);
+        }
+    }
+
+    sentence
+}
+
+/// End a question or exclamation, opening it with an inverted mark in languages that use them.
+fn close(sentence: String, inverted: &str, mark: &str, typography: &Typography) -> String {
+    let opening = if typography.inverted_marks { inverted } else { "" };
+    format!("{}{}{}{}", opening, sentence, typography.punctuation_space, mark)
+}
+
+/// A run of `min_len` to `max_len` words lying within `lower..upper`.
+fn span<R: Rng + ?Sized>(rng: &mut R, lower: usize, upper: usize, min_len: usize, max_len: usize) -> (usize, usize) {
+    let room = upper - lower;
+    let len = rng.gen_range(min_len as u64..=max_len as u64).min(room as u64) as usize;
+    let start = lower + rng.gen_range(0..=(room - len) as u64) as usize;
+    (start, start + len)
+}
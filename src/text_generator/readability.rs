@@ -0,0 +1,262 @@
+//! Readability metrics and reading-level targets.  Text can be scored by Flesch reading ease, Flesch–Kincaid grade and LIX, and paragraphs can be generated to land in a band of any of them, for copy written to a given reading level.
+//!
+//! Syllables are estimated from vowel groups, tuned for English.  Kana, Han and Hangul count a syllable per character, Devanagari a syllable per vowel, and other scripts one per two letters, so scores for languages other than English are only rough.  LIX, which counts words over six letters instead of syllables, suits Swedish and German best.
+
+use rand::Rng;
+use serde::Serialize;
+use std::fmt::Display;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::random_index;
+use super::stats::Script;
+
+/// Counts behind the readability scores of a text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Readability {
+    /// Sentences holding at least one word
+    pub sentences: usize,
+    pub words: usize,
+
+    /// Estimated syllables, summed over every word
+    pub syllables: usize,
+
+    /// Words of more than six letters, as LIX counts them
+    pub long_words: usize,
+}
+
+impl Readability {
+    /// Score a text.  Sentences and words are found at Unicode boundaries.
+    pub fn of(text: &str) -> Readability {
+        let mut readability = Readability::default();
+        for sentence in text.unicode_sentences() {
+            let mut words = sentence.unicode_words().peekable();
+            if words.peek().is_none() {
+                continue;
+            }
+            readability.sentences += 1;
+            for word in words {
+                readability.words += 1;
+                readability.syllables += syllables(word);
+                if is_long(word) {
+                    readability.long_words += 1;
+                }
+            }
+        }
+        readability
+    }
+
+    /// Mean words per sentence, or 0 if there are no sentences.
+    pub fn average_sentence_length(&self) -> f64 {
+        ratio(self.words, self.sentences)
+    }
+
+    /// Mean syllables per word, or 0 if there are no words.
+    pub fn syllables_per_word(&self) -> f64 {
+        ratio(self.syllables, self.words)
+    }
+
+    /// Flesch reading ease: about 90 to 100 is very easy, 60 to 70 plain English and below 30 very hard.
+    pub fn flesch_reading_ease(&self) -> f64 {
+        206.835 - 1.015 * self.average_sentence_length() - 84.6 * self.syllables_per_word()
+    }
+
+    /// Flesch–Kincaid grade: the US school grade needed to follow the text.
+    pub fn flesch_kincaid_grade(&self) -> f64 {
+        0.39 * self.average_sentence_length() + 11.8 * self.syllables_per_word() - 15.59
+    }
+
+    /// LIX: words per sentence plus the percentage of long words.  Below 30 is very easy, 40 to 50 is ordinary prose and above 60 very hard.
+    pub fn lix(&self) -> f64 {
+        self.average_sentence_length() + 100.0 * ratio(self.long_words, self.words)
+    }
+}
+
+fn ratio(n: usize, d: usize) -> f64 {
+    if d == 0 {
+        return 0.0;
+    }
+    n as f64 / d as f64
+}
+
+fn is_long(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).count() > 6
+}
+
+impl Display for Readability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Sentences:            {}", self.sentences)?;
+        writeln!(f, "Words:                {}", self.words)?;
+        writeln!(f, "Syllables:            {}", self.syllables)?;
+        writeln!(f, "Words per sentence:   {:.2}", self.average_sentence_length())?;
+        writeln!(f, "Syllables per word:   {:.2}", self.syllables_per_word())?;
+        writeln!(f, "Flesch reading ease:  {:.1}", self.flesch_reading_ease())?;
+        writeln!(f, "Flesch–Kincaid grade: {:.1}", self.flesch_kincaid_grade())?;
+        write!(f, "LIX:                  {:.1}", self.lix())
+    }
+}
+
+const VOWELS: &str = "aeiouyàáâãäåæèéêëìíîïòóôõöøùúûüýÿœαεηιουωάέήίόύώϊϋΐΰаеёиоуыэюяіїєў";
+
+/// Estimate the syllables of a word.  Any word with a letter has at least one.
+pub fn syllables(word: &str) -> usize {
+    let lower = word.to_lowercase();
+    let chars: Vec<char> = lower.chars().collect();
+    let mut count = 0;
+    let mut other_letters: usize = 0;
+    let mut in_vowel = false;
+    for (i, &c) in chars.iter().enumerate() {
+        let vowel = VOWELS.contains(c);
+        match Script::of(c) {
+            Script::Latin | Script::Greek | Script::Cyrillic if vowel && !in_vowel => count += 1,
+            Script::Latin | Script::Greek | Script::Cyrillic => (),
+            // small kana and the long vowel mark extend the syllable before them
+            Script::Hiragana | Script::Katakana if "ぁぃぅぇぉゃゅょっゎァィゥェォャュョッヮー".contains(c) => (),
+            Script::Han | Script::Hiragana | Script::Katakana | Script::Hangul => count += 1,
+            // independent vowels, and consonants not silenced by a virama
+            Script::Devanagari => match c as u32 {
+                0x0904..=0x0914 | 0x0960..=0x0961 => count += 1,
+                0x0915..=0x0939 | 0x0958..=0x095F if chars.get(i + 1) != Some(&'\u{094D}') => count += 1,
+                _ => ()
+            },
+            _ if c.is_alphabetic() => other_letters += 1,
+            _ => ()
+        }
+        in_vowel = vowel;
+    }
+
+    // a silent final e, as in "cake", but not a syllabic "le", as in "table"
+    if count > 1 && lower.is_ascii() && lower.ends_with('e') && !lower.ends_with("le") && !VOWELS.contains(chars[chars.len() - 2]) {
+        count -= 1;
+    }
+    count += other_letters.div_ceil(2);
+
+    if count == 0 && word.chars().any(char::is_alphabetic) {
+        return 1;
+    }
+    count
+}
+
+/// A readability band to aim generated text at.  Bounds are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum ReadingLevel {
+    /// Flesch reading ease, e.g. 60 to 70 for plain English
+    FleschReadingEase { min: f64, max: f64 },
+
+    /// Flesch–Kincaid grade, e.g. 6 to 8 for most public-facing copy
+    FleschKincaidGrade { min: f64, max: f64 },
+
+    /// LIX, e.g. 30 to 40 for easy Swedish or German
+    Lix { min: f64, max: f64 },
+}
+
+impl ReadingLevel {
+    fn bounds(&self) -> (f64, f64) {
+        match *self {
+            ReadingLevel::FleschReadingEase { min, max } | ReadingLevel::FleschKincaidGrade { min, max } | ReadingLevel::Lix { min, max } => (min, max),
+        }
+    }
+
+    /// The score of a text on this level's scale.
+    pub fn score(&self, readability: &Readability) -> f64 {
+        match self {
+            ReadingLevel::FleschReadingEase { .. } => readability.flesch_reading_ease(),
+            ReadingLevel::FleschKincaidGrade { .. } => readability.flesch_kincaid_grade(),
+            ReadingLevel::Lix { .. } => readability.lix(),
+        }
+    }
+
+    /// True if a text scores within the band.
+    pub fn contains(&self, readability: &Readability) -> bool {
+        let (min, max) = self.bounds();
+        (min..=max).contains(&self.score(readability))
+    }
+
+    /// Check that the band is finite and not inverted.
+    pub(crate) fn validate(&self) -> Result<(), GeneratorError> {
+        let (min, max) = self.bounds();
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Err(GeneratorError::InvalidRange(format!("reading level band {} to {} is not a range", min, max)));
+        }
+        Ok(())
+    }
+
+    /// The sentence length and word measure that put text in the middle of the band.  Sentence length moves with difficulty, and word choice makes up the rest of the score.
+    pub(crate) fn target(&self) -> ReadingTarget {
+        let (min, max) = self.bounds();
+        let mid = (min + max) / 2.0;
+        match self {
+            ReadingLevel::FleschReadingEase { .. } => {
+                let words_per_sentence = (6.0 + (100.0 - mid) / 5.0).clamp(5.0, 25.0);
+                let syllables = (206.835 - 1.015 * words_per_sentence - mid) / 84.6;
+                ReadingTarget { words_per_sentence, measure: WordMeasure::Syllables(syllables.clamp(1.0, 3.0)) }
+            },
+            ReadingLevel::FleschKincaidGrade { .. } => {
+                let words_per_sentence = (6.0 + mid).clamp(5.0, 25.0);
+                let syllables = (mid + 15.59 - 0.39 * words_per_sentence) / 11.8;
+                ReadingTarget { words_per_sentence, measure: WordMeasure::Syllables(syllables.clamp(1.0, 3.0)) }
+            },
+            ReadingLevel::Lix { .. } => {
+                let words_per_sentence = (mid / 3.0).clamp(5.0, 25.0);
+                let long = (mid - words_per_sentence) / 100.0;
+                ReadingTarget { words_per_sentence, measure: WordMeasure::LongWords(long.clamp(0.0, 1.0)) }
+            },
+        }
+    }
+}
+
+/// What sentences are steered towards to meet a reading level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ReadingTarget {
+    words_per_sentence: f64,
+    measure: WordMeasure,
+}
+
+/// The per-word quantity a reading level depends on, and its target mean.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WordMeasure {
+    Syllables(f64),
+
+    /// The share of words over six letters
+    LongWords(f64),
+}
+
+/// Candidate words drawn for each word of a sentence.  The one that keeps the sentence closest to its target is used.
+const CANDIDATES: usize = 8;
+
+impl ReadingTarget {
+    /// The words of one sentence.  `draw` picks the index of a word as the sampling would.  Among candidates that fit equally well, the best-ranked word wins.
+    pub(crate) fn sentence<R: Rng + ?Sized>(&self, word_list: &[String], draw: impl Fn(&mut R) -> usize, rng: &mut R) -> Vec<String> {
+        // two words either side of the target on average
+        let base = self.words_per_sentence.floor() as usize;
+        let extra = rng.gen_bool(self.words_per_sentence.fract()) as usize;
+        let n_words = (base + extra + random_index(rng, 5)).saturating_sub(2).max(2);
+
+        let (target, per_word): (f64, fn(&str) -> usize) = match self.measure {
+            WordMeasure::Syllables(s) => (s, syllables),
+            WordMeasure::LongWords(share) => (share, |w| is_long(w) as usize),
+        };
+
+        let mut words = Vec::new();
+        let (mut n, mut total) = (0, 0);
+        while n < n_words {
+            let best = (0..CANDIDATES)
+                .map(|_| draw(rng))
+                .map(|i| {
+                    // an entry may hold several words
+                    let entry_words = word_list[i].unicode_words().count().max(1);
+                    let measure: usize = word_list[i].unicode_words().map(per_word).sum();
+                    let miss = (target * (n + entry_words) as f64 - (total + measure) as f64).abs();
+                    (miss, i, entry_words, measure)
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            let Some((_, i, entry_words, measure)) = best else {
+                break;
+            };
+            words.push(word_list[i].to_owned());
+            n += entry_words;
+            total += measure;
+        }
+        words
+    }
+}
@@ -0,0 +1,583 @@
+//! Placeholder translations of i18n resource files.  A source file of keys and English values is read, and a matching file is written for each language with every value replaced by generated text of a similar length from that language's corpus, so layouts can be checked in every locale long before real translations exist.
+//!
+//! JSON, YAML, gettext .po and Fluent .ftl files are supported.  Keys, key order and structure are kept in every format, and comments in .po and .ftl files.  YAML files are parsed and written again, so their comments, anchors and quoting are lost.  Placeholders inside values, such as `{name}`, `{count, plural, ...}`, `%s` or Fluent's `{ $name }`, are copied as they are.
+
+use rand::Rng;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::pseudo_locale::{self, Piece};
+use super::error::GeneratorError;
+use super::language::Language;
+use super::length::{Boundary, LengthTarget};
+use super::output::{Metadata, OutputWriter, WriteMode};
+use super::typography::Typography;
+use super::{build_paragraph, load_source, seeded_rng, Corpus, ParagraphOptions, Source};
+
+/// The format of a resource file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceFormat {
+    /// Nested JSON objects with string values, as used by i18next and most web frameworks
+    Json,
+
+    /// Nested YAML mappings with string values, as used by Rails.  A single top-level key naming a language, such as `en:`, is renamed for each language.
+    Yaml,
+
+    /// A gettext catalog or template (.po or .pot).  Each msgstr is filled from its msgid, and plural forms from msgid_plural.
+    Po,
+
+    /// A Fluent file (.ftl).  Messages, terms, attributes and the variants of select expressions are filled.
+    Fluent,
+}
+
+impl ResourceFormat {
+    /// The format of a file, going by its extension, or None if the extension is not recognized.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<ResourceFormat> {
+        let extension = path.as_ref().extension().map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("json") => Some(ResourceFormat::Json),
+            Some("yaml") | Some("yml") => Some(ResourceFormat::Yaml),
+            Some("po") | Some("pot") => Some(ResourceFormat::Po),
+            Some("ftl") => Some(ResourceFormat::Fluent),
+            _ => None
+        }
+    }
+
+    /// The extension of the files written in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ResourceFormat::Json => "json",
+            ResourceFormat::Yaml => "yaml",
+            ResourceFormat::Po => "po",
+            ResourceFormat::Fluent => "ftl",
+        }
+    }
+}
+
+/// Options for generated resource files.
+#[derive(Clone, Debug)]
+pub struct ResourceOptions {
+    /// The languages to write a file for
+    languages: Vec<Language>,
+
+    /// The length of each generated value, as a percentage of the length of the source value
+    expansion: u32,
+
+    /// Options for the generated text.  Its length target is set for each value.
+    paragraph: ParagraphOptions,
+}
+
+impl Default for ResourceOptions {
+    fn default() -> Self {
+        ResourceOptions { languages: Language::ALL.to_vec(), expansion: 100, paragraph: ParagraphOptions::default() }
+    }
+}
+
+impl ResourceOptions {
+    /// Every bundled language, with values as long as the source values.
+    pub fn new() -> ResourceOptions {
+        ResourceOptions::default()
+    }
+
+    /// Set the languages to write a file for.
+    pub fn set_languages(mut self, languages: &[Language]) -> ResourceOptions {
+        self.languages = languages.to_vec();
+        self
+    }
+
+    /// Set the length of generated values as a percentage of the source values, e.g. 130 to leave room for languages that run longer than English.
+    pub fn set_expansion(mut self, percent: u32) -> ResourceOptions {
+        self.expansion = percent;
+        self
+    }
+
+    /// Set the options for the generated text, e.g. its sampling or boundary.
+    pub fn set_paragraph_options(mut self, options: ParagraphOptions) -> ResourceOptions {
+        self.paragraph = options;
+        self
+    }
+}
+
+/// A parsed source resource file.
+#[derive(Clone, Debug)]
+pub struct ResourceFile {
+    format: ResourceFormat,
+    contents: Contents,
+}
+
+#[derive(Clone, Debug)]
+enum Contents {
+    Json(JsonNode),
+    Yaml(serde_yaml::Value),
+
+    /// .po and .ftl files are filled line by line, so comments and layout are kept exactly
+    Lines(String),
+}
+
+impl ResourceFile {
+    /// Parse a resource file, or return [GeneratorError::InvalidResource] if it is malformed.
+    pub fn parse(text: &str, format: ResourceFormat) -> Result<ResourceFile, GeneratorError> {
+        let contents = match format {
+            ResourceFormat::Json => Contents::Json(serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?),
+            ResourceFormat::Yaml => Contents::Yaml(serde_yaml::from_str(text).map_err(|e| invalid(e.to_string()))?),
+            ResourceFormat::Po => {
+                fill_po(text, None, &mut |s| s.to_owned())?;
+                Contents::Lines(text.to_owned())
+            },
+            ResourceFormat::Fluent => {
+                fill_fluent(text, &mut |s| s.to_owned())?;
+                Contents::Lines(text.to_owned())
+            },
+        };
+        Ok(ResourceFile { format, contents })
+    }
+
+    /// Read a resource file, in the format given by its extension.
+    pub fn read(path: impl AsRef<Path>) -> Result<ResourceFile, GeneratorError> {
+        let path = path.as_ref();
+        let format = ResourceFormat::from_path(path)
+            .ok_or_else(|| invalid(format!("{} is not a JSON, YAML, .po or .ftl file", path.display())))?;
+        ResourceFile::parse(&fs::read_to_string(path)?, format)
+    }
+
+    /// The format of the file.
+    pub fn format(&self) -> ResourceFormat {
+        self.format
+    }
+
+    /// The file with its values replaced by text in a language, drawing all randomness from the caller's random number generator.
+    pub fn localize<R: Rng + ?Sized>(&self, language: Language, options: &ResourceOptions, rng: &mut R) -> Result<String, GeneratorError> {
+        options.paragraph.validate()?;
+        let mut filler = Filler {
+            source: load_source(Corpus::Embedded(language), &options.paragraph.sampling, options.paragraph.reading_level.as_ref())?,
+            typography: options.paragraph.typography_for(Some(language)),
+            options,
+            rng,
+        };
+
+        match &self.contents {
+            Contents::Json(value) => {
+                let mut value = value.clone();
+                fill_json(&mut value, &mut filler);
+                let mut text = serde_json::to_string_pretty(&value).map_err(|e| invalid(e.to_string()))?;
+                text.push('\n');
+                Ok(text)
+            },
+            Contents::Yaml(value) => {
+                let mut value = value.clone();
+                fill_yaml(&mut value, &mut filler);
+                rename_root(&mut value, language);
+                serde_yaml::to_string(&value).map_err(|e| invalid(e.to_string()))
+            },
+            Contents::Lines(text) => match self.format {
+                ResourceFormat::Fluent => fill_fluent(text, &mut |s| filler.message(s, fluent_pieces)),
+                _ => fill_po(text, Some(language), &mut |s| filler.message(s, pseudo_locale::split)),
+            },
+        }
+    }
+}
+
+/// Read a source resource file and write a filled-in copy for each language, named by language code, e.g. `fr.json`, into `out_dir`.  Existing files with the same names are replaced.
+/// Each file is generated from the same seed, so a language's file does not change when other languages are added or removed.
+pub fn generate_resources(source: impl AsRef<Path>, out_dir: impl AsRef<Path>, options: &ResourceOptions, seed: u64) -> Result<Vec<PathBuf>, GeneratorError> {
+    let resource = ResourceFile::read(source)?;
+    let writer = OutputWriter::new()
+        .set_mode(WriteMode::Overwrite)
+        .set_create_dirs(true);
+
+    let mut paths = Vec::new();
+    for &language in &options.languages {
+        let text = resource.localize(language, options, &mut seeded_rng(seed))?;
+        let path = out_dir.as_ref().join(format!("{}.{}", language.code(), resource.format.extension()));
+        paths.push(writer.write(path, &text, &Metadata::new().set_language(language).set_seed(seed))?);
+    }
+    Ok(paths)
+}
+
+fn invalid(message: impl Into<String>) -> GeneratorError {
+    GeneratorError::InvalidResource(message.into())
+}
+
+/// Generates replacement text for the values of one file.
+struct Filler<'a, R: ?Sized> {
+    source: Source,
+    typography: Typography,
+    options: &'a ResourceOptions,
+    rng: &'a mut R,
+}
+
+impl<R: Rng + ?Sized> Filler<'_, R> {
+    /// Replace the text of a value, keeping its placeholders.
+    fn message(&mut self, value: &str, split: fn(&str) -> Vec<Piece>) -> String {
+        split(value).into_iter()
+            .map(|piece| match piece {
+                Piece::Text(t) => self.text(&t),
+                Piece::Placeholder(p) => p,
+            })
+            .collect()
+    }
+
+    /// Generated text with as many grapheme clusters as a run of text, scaled by the expansion.  Surrounding whitespace and a closing full stop, comma, question mark or exclamation mark are kept, in the language's own form.  Runs without letters are left alone.
+    fn text(&mut self, text: &str) -> String {
+        if !text.chars().any(char::is_alphabetic) {
+            return text.to_owned();
+        }
+        let body = text.trim();
+        let lead = &text[..text.len() - text.trim_start().len()];
+        let trail = &text[text.trim_end().len()..];
+
+        let typography = &self.typography;
+        let (body, mark) = match body.chars().last() {
+            Some('.') => (&body[..body.len() - 1], typography.full_stop.to_owned()),
+            Some('?') => (&body[..body.len() - 1], format!("{}{}", typography.punctuation_space, typography.question_mark)),
+            Some('!') => (&body[..body.len() - 1], format!("{}{}", typography.punctuation_space, typography.exclamation_mark)),
+            Some(',') => (&body[..body.len() - 1], typography.comma.to_owned()),
+            _ => (body, String::new()),
+        };
+
+        let length = (body.graphemes(true).count() * self.options.expansion as usize).div_ceil(100).max(1);
+        let options = self.options.paragraph.clone()
+            .set_length(LengthTarget::Graphemes(length))
+            .set_boundary(Boundary::Word);
+        let mut generated = build_paragraph(&self.source, typography, &options, self.rng);
+        // a first word longer than the value has to be cut
+        if generated.is_empty() {
+            generated = build_paragraph(&self.source, typography, &options.set_boundary(Boundary::None), self.rng);
+        }
+        if !typography.full_stop.is_empty() && generated.ends_with(typography.full_stop) {
+            generated.truncate(generated.len() - typography.full_stop.len());
+        }
+
+        format!("{}{}{}{}", lead, generated, mark, trail)
+    }
+}
+
+/// A JSON value whose objects keep their keys in source order.  `serde_json::Value` sorts keys unless serde_json's `preserve_order` feature is on, and a library can't turn that on without changing it for every crate built alongside.
+#[derive(Clone, Debug)]
+enum JsonNode {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<JsonNode>),
+    Object(Vec<(String, JsonNode)>),
+}
+
+impl<'de> Deserialize<'de> for JsonNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<JsonNode, D::Error> {
+        deserializer.deserialize_any(JsonNodeVisitor)
+    }
+}
+
+struct JsonNodeVisitor;
+
+impl<'de> Visitor<'de> for JsonNodeVisitor {
+    type Value = JsonNode;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonNode, E> {
+        Ok(JsonNode::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<JsonNode, E> {
+        Ok(JsonNode::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<JsonNode, E> {
+        Ok(JsonNode::Number(n.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<JsonNode, E> {
+        Ok(JsonNode::Number(n.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<JsonNode, E> {
+        serde_json::Number::from_f64(n).map(JsonNode::Number).ok_or_else(|| E::custom("number is not finite"))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<JsonNode, E> {
+        Ok(JsonNode::String(s.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<JsonNode, E> {
+        Ok(JsonNode::String(s))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonNode, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonNode::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonNode, A::Error> {
+        let mut entries: Vec<(String, JsonNode)> = Vec::new();
+        while let Some((key, value)) = map.next_entry()? {
+            // a repeated key replaces the earlier value in place, as serde_json does
+            match entries.iter_mut().find(|(k, _)| *k == key) {
+                Some(entry) => entry.1 = value,
+                None => entries.push((key, value)),
+            }
+        }
+        Ok(JsonNode::Object(entries))
+    }
+}
+
+impl Serialize for JsonNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonNode::Null => serializer.serialize_unit(),
+            JsonNode::Bool(b) => serializer.serialize_bool(*b),
+            JsonNode::Number(n) => n.serialize(serializer),
+            JsonNode::String(s) => serializer.serialize_str(s),
+            JsonNode::Array(items) => items.serialize(serializer),
+            JsonNode::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            },
+        }
+    }
+}
+
+fn fill_json<R: Rng + ?Sized>(value: &mut JsonNode, filler: &mut Filler<'_, R>) {
+    match value {
+        JsonNode::String(s) => *s = filler.message(s, pseudo_locale::split),
+        JsonNode::Array(items) => items.iter_mut().for_each(|v| fill_json(v, filler)),
+        JsonNode::Object(entries) => entries.iter_mut().for_each(|(_, v)| fill_json(v, filler)),
+        _ => ()
+    }
+}
+
+fn fill_yaml<R: Rng + ?Sized>(value: &mut serde_yaml::Value, filler: &mut Filler<'_, R>) {
+    match value {
+        serde_yaml::Value::String(s) => *s = filler.message(s, pseudo_locale::split),
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(|v| fill_yaml(v, filler)),
+        serde_yaml::Value::Mapping(map) => map.values_mut().for_each(|v| fill_yaml(v, filler)),
+        serde_yaml::Value::Tagged(tagged) => fill_yaml(&mut tagged.value, filler),
+        _ => ()
+    }
+}
+
+/// Rename a lone top-level key naming a language, as in Rails' `en:`, to the code of the target language.
+fn rename_root(value: &mut serde_yaml::Value, language: Language) {
+    let serde_yaml::Value::Mapping(map) = value else {
+        return;
+    };
+    let is_language = map.len() == 1 && map.keys().next().and_then(|k| k.as_str()).is_some_and(|k| k.parse::<Language>().is_ok());
+    if is_language {
+        let key = map.keys().next().cloned().unwrap_or_default();
+        if let Some(contents) = map.remove(&key) {
+            map.insert(serde_yaml::Value::from(language.code()), contents);
+        }
+    }
+}
+
+/// Fill each msgstr of a gettext catalog from its msgid, or check the catalog when `language` is None.  Entries, comments and obsolete entries are copied as they are, and the header's Language field is set.
+fn fill_po(text: &str, language: Option<Language>, fill: &mut dyn FnMut(&str) -> String) -> Result<String, GeneratorError> {
+    let mut out = String::new();
+    let mut entry = PoEntry::default();
+    for (n, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            entry.finish(&mut out, language, fill)?;
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            entry.kept.push(line.to_owned());
+            continue;
+        }
+
+        let (keyword, quoted) = match trimmed.split_once(char::is_whitespace) {
+            _ if trimmed.starts_with('"') => (None, trimmed),
+            Some((keyword, quoted)) => (Some(keyword), quoted.trim()),
+            None => return Err(invalid(format!("line {}: expected a keyword and a string", n + 1))),
+        };
+        let value = po_unquote(quoted).ok_or_else(|| invalid(format!("line {}: expected a quoted string", n + 1)))?;
+        let is_msgstr = match keyword {
+            // a continuation of the string above
+            None => match entry.fields.last_mut() {
+                Some((keyword, v)) => {
+                    v.push_str(&value);
+                    keyword.starts_with("msgstr")
+                },
+                None => return Err(invalid(format!("line {}: string outside of an entry", n + 1))),
+            },
+            Some(keyword) if matches!(keyword, "msgctxt" | "msgid" | "msgid_plural") || keyword.starts_with("msgstr") => {
+                entry.fields.push((keyword.to_owned(), value));
+                keyword.starts_with("msgstr")
+            },
+            Some(keyword) => return Err(invalid(format!("line {}: unknown keyword {}", n + 1, keyword))),
+        };
+        if !is_msgstr {
+            entry.kept.push(line.to_owned());
+        }
+    }
+    entry.finish(&mut out, language, fill)?;
+    Ok(out)
+}
+
+/// The lines of one .po entry read so far.
+#[derive(Default)]
+struct PoEntry {
+    /// Comments, msgctxt, msgid and msgid_plural lines, copied as they are
+    kept: Vec<String>,
+
+    /// Every keyword and its unescaped string, in order
+    fields: Vec<(String, String)>,
+}
+
+impl PoEntry {
+    fn field(&self, keyword: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == keyword).map(|(_, v)| v.as_str())
+    }
+
+    /// Write the entry with its msgstr filled in, and start a new one.
+    fn finish(&mut self, out: &mut String, language: Option<Language>, fill: &mut dyn FnMut(&str) -> String) -> Result<(), GeneratorError> {
+        let entry = std::mem::take(self);
+        for line in &entry.kept {
+            out.push_str(line);
+            out.push('\n');
+        }
+        let msgid = match entry.field("msgid") {
+            Some(msgid) => msgid,
+            None if entry.fields.is_empty() => return Ok(()),
+            None => return Err(invalid(format!("msgstr without a msgid after \"{}\"", entry.kept.last().map_or("", |l| l.as_str())))),
+        };
+
+        for (keyword, value) in entry.fields.iter().filter(|(k, _)| k.starts_with("msgstr")) {
+            let filled = if msgid.is_empty() {
+                // the header
+                match language {
+                    Some(l) => po_header(value, l),
+                    None => value.to_owned(),
+                }
+            } else if keyword == "msgstr" || keyword == "msgstr[0]" {
+                fill(msgid)
+            } else {
+                fill(entry.field("msgid_plural").unwrap_or(msgid))
+            };
+            out.push_str(&format!("{} {}\n", keyword, po_quote(&filled)));
+        }
+        Ok(())
+    }
+}
+
+/// The header with its Language field set, added if it is missing.
+fn po_header(header: &str, language: Language) -> String {
+    let mut lines: Vec<String> = header.lines()
+        .filter(|l| !l.starts_with("Language:"))
+        .map(|l| format!("{}\n", l))
+        .collect();
+    lines.push(format!("Language: {}\n", language.code()));
+    lines.concat()
+}
+
+fn po_unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            other => value.push(other),
+        }
+    }
+    Some(value)
+}
+
+fn po_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Fill the messages, terms and attributes of a Fluent file.  Each variant of a select expression is filled on its own line, and continuation lines are filled one by one.
+fn fill_fluent(text: &str, fill: &mut dyn FnMut(&str) -> String) -> Result<String, GeneratorError> {
+    let mut out = String::new();
+    for (n, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let filled = if trimmed.is_empty() || line.starts_with('#') {
+            line.to_owned()
+        } else if indent.is_empty() {
+            // message-id = pattern, or -term-id = pattern
+            let (id, pattern) = line.split_once('=')
+                .filter(|(id, _)| is_fluent_id(id.trim().trim_start_matches('-')))
+                .ok_or_else(|| invalid(format!("line {}: expected an identifier followed by =", n + 1)))?;
+            format!("{}={}", id, fill(pattern))
+        } else if let Some((attribute, pattern)) = trimmed.split_once('=').filter(|(a, _)| a.starts_with('.')) {
+            format!("{}{}={}", indent, attribute, fill(pattern))
+        } else if trimmed.starts_with('[') || trimmed.starts_with("*[") {
+            let key_end = trimmed.find(']').ok_or_else(|| invalid(format!("line {}: unclosed variant key", n + 1)))? + 1;
+            format!("{}{}{}", indent, &trimmed[..key_end], fill(&trimmed[key_end..]))
+        } else if let Some(rest) = trimmed.strip_prefix('}') {
+            format!("{}}}{}", indent, fill(rest))
+        } else {
+            format!("{}{}", indent, fill(trimmed))
+        };
+        out.push_str(&filled);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn is_fluent_id(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphabetic()) && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Split a line of a Fluent pattern into text and placeables.  A placeable left open at the end of the line, such as the start of a select expression, runs to the end of the line.
+fn fluent_pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            pieces.push(Piece::Text(rest[..start].to_owned()));
+        }
+        let mut nesting = 0;
+        let end = rest[start..].char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '{' => nesting += 1,
+                    '}' => nesting -= 1,
+                    _ => ()
+                }
+                nesting == 0
+            })
+            .map_or(rest.len(), |(i, _)| start + i + 1);
+        pieces.push(Piece::Placeholder(rest[start..end].to_owned()));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest.to_owned()));
+    }
+    pieces
+}
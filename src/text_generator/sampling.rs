@@ -0,0 +1,102 @@
+//! How words are drawn from a word list.  Word lists in `corpus/` are ordered by frequency, so weighting words by rank gives generated text realistic word-frequency statistics.
+
+use rand::distributions::WeightedIndex;
+use serde::Serialize;
+
+use super::error::GeneratorError;
+
+/// How words are drawn from a word list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub enum Sampling {
+    /// Every line of the word list is equally likely.
+    #[default]
+    Uniform,
+
+    /// Words are weighted by frequency.  If every line of the word list has a frequency column ("word\tcount"), a word's weight is its count raised to `exponent`.
+    /// Otherwise the list is taken to be ordered from most to least frequent, and the word of rank r has weight 1 / r^`exponent`.  An exponent of 1 follows Zipf's law.  The exponent must be a finite number.
+    Zipf { exponent: f64 },
+}
+
+/// Fractional bits of the fixed-point logarithms weights are computed with.  Weights use integer arithmetic only, rather than `f64::powf`, whose result depends on the platform's maths library, so a seed gives the same text everywhere.
+const FRACTION_BITS: u32 = 32;
+
+/// The heaviest word has weight 2^`WEIGHT_BITS`, and every other word is scaled to match.
+const WEIGHT_BITS: i128 = 32;
+
+/// Exponents are clamped to this size.  Beyond it every word but the first has the least weight anyway.
+const MAX_EXPONENT: f64 = 1024.0;
+
+/// 2^(2^-j) for j from 1 to 32, with 62 fractional bits.
+const EXP2_FRACTIONS: [u128; 32] = [
+    0x5A827999FCEF3242, 0x4C1BF828C6DC54B8, 0x45CAE0F1F545EB73, 0x42D561B3E6243D8A,
+    0x4166C34C5615D0EC, 0x40B268F9DE0183BA, 0x4058F6A7ECCCD5B6, 0x402C6BE96AF2FB58,
+    0x4016321B687027A8, 0x400B18178BA33B14, 0x40058BCE410147E8, 0x4002C5D7BFF71DAF,
+    0x400162E807EE7E5B, 0x4000B1730DF6A524, 0x400058B9497B8152, 0x40002C5C955DD701,
+    0x4000162E46D6F26C, 0x40000B1722757B1B, 0x4000058B90FD3E0C, 0x400002C5C86F3F26,
+    0x40000162E433C79B, 0x400000B17218EDD0, 0x40000058B90C3968, 0x4000002C5C860D54,
+    0x400000162E4302D2, 0x4000000B17218073, 0x400000058B90BFFC, 0x40000002C5C85FEF,
+    0x4000000162E42FF3, 0x40000000B17217F9, 0x4000000058B90BFC, 0x400000002C5C85FE,
+];
+
+/// Build the distribution words are drawn from, or `None` for uniform sampling.  Fails if the exponent is not a finite number.
+pub(crate) fn weighted_index(sampling: &Sampling, len: usize, counts: Option<&[u64]>) -> Result<Option<WeightedIndex<u64>>, GeneratorError> {
+    let exponent = match sampling {
+        Sampling::Uniform => return Ok(None),
+        Sampling::Zipf { exponent } => *exponent,
+    };
+    if !exponent.is_finite() {
+        return Err(GeneratorError::InvalidRange(format!("Zipf exponent {} is not a finite number", exponent)));
+    }
+    // scaling by a power of two and rounding are exact, so this is the same on every platform
+    let exponent = (exponent.clamp(-MAX_EXPONENT, MAX_EXPONENT) * (1u64 << FRACTION_BITS) as f64).round() as i128;
+
+    // the log2 of each weight, or None for a count of zero
+    let logs: Vec<Option<i128>> = match counts {
+        Some(c) => c.iter().map(|&count| (count > 0).then(|| (exponent * log2(count)) >> FRACTION_BITS)).collect(),
+        None => (1..=len as u64).map(|rank| Some((-exponent * log2(rank)) >> FRACTION_BITS)).collect(),
+    };
+    let Some(max) = logs.iter().flatten().max().copied() else {
+        return Ok(None);
+    };
+
+    // every word keeps a weight of at least 1 so that none of them disappear
+    let scaled = logs.iter().map(|log| log.map_or(1, |l| exp2((WEIGHT_BITS << FRACTION_BITS) + l - max).max(1)));
+    Ok(WeightedIndex::new(scaled).ok())
+}
+
+/// The base-2 logarithm of a positive integer, with `FRACTION_BITS` fractional bits.
+fn log2(x: u64) -> i128 {
+    let whole = 63 - x.leading_zeros();
+    // x / 2^whole, which lies in [1, 2), with 62 fractional bits
+    let mut mantissa = match whole {
+        0..=62 => (x as u128) << (62 - whole),
+        _ => (x as u128) >> (whole - 62),
+    };
+
+    // squaring the mantissa doubles its logarithm, so each square gives the next bit
+    let mut log = (whole as i128) << FRACTION_BITS;
+    for bit in (0..FRACTION_BITS).rev() {
+        mantissa = (mantissa * mantissa) >> 62;
+        if mantissa >= 2 << 62 {
+            mantissa >>= 1;
+            log |= 1 << bit;
+        }
+    }
+    log
+}
+
+/// 2 raised to a power with `FRACTION_BITS` fractional bits, rounded to an integer.  Powers above `WEIGHT_BITS` are not supported.
+fn exp2(power: i128) -> u64 {
+    if power < 0 {
+        return 0;
+    }
+    let whole = (power >> FRACTION_BITS) as u32;
+
+    let mut result: u128 = 1 << 62;
+    for (j, factor) in EXP2_FRACTIONS.iter().enumerate() {
+        if (power >> (FRACTION_BITS - 1 - j as u32)) & 1 == 1 {
+            result = (result * factor) >> 62;
+        }
+    }
+    (((result >> (61 - whole)) + 1) >> 1) as u64
+}
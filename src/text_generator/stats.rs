@@ -0,0 +1,202 @@
+//! Statistics for inspecting a corpus before generating from it.  The report counts blank, padded and duplicate lines, measures word lengths and lists the characters and scripts in use.  It flags words that mix scripts, so a bad corpus can be caught in review.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::{Contents, Corpus};
+
+/// The writing system of a character, coarse enough to cover the bundled corpora.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Han,
+
+    /// Digits, punctuation, symbols and spaces, which every script uses
+    Common,
+
+    /// Combining marks, which take the script of the letter they sit on
+    Inherited,
+
+    /// Any other script
+    Other,
+}
+
+impl Script {
+    /// The script of a character.
+    pub fn of(c: char) -> Script {
+        match c as u32 {
+            0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F => Script::Inherited,
+            0x0041..=0x005A | 0x0061..=0x007A | 0x00AA | 0x00BA | 0x00C0..=0x00D6 | 0x00D8..=0x00F6 | 0x00F8..=0x024F
+                | 0x1E00..=0x1EFF | 0x2C60..=0x2C7F | 0xA720..=0xA7FF | 0xFF21..=0xFF3A | 0xFF41..=0xFF5A => Script::Latin,
+            0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+            0x0400..=0x052F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+            0x0590..=0x05FF => Script::Hebrew,
+            0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => Script::Arabic,
+            0x0900..=0x097F | 0xA8E0..=0xA8FF => Script::Devanagari,
+            0x0E00..=0x0E7F => Script::Thai,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+            0x3040..=0x309F => Script::Hiragana,
+            0x30A0..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Katakana,
+            0x2E80..=0x2FDF | 0x3005 | 0x3007 | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3134F => Script::Han,
+            _ if c.is_alphabetic() => Script::Other,
+            _ => Script::Common,
+        }
+    }
+
+    /// Japanese mixes Han, Hiragana and Katakana within words, and Korean mixes Han and Hangul, so those are not counted as mixed scripts.
+    fn writes_with(self, other: Script) -> bool {
+        use Script::*;
+        matches!(
+            (self, other),
+            (Han, Hiragana) | (Han, Katakana) | (Hiragana, Katakana) | (Hiragana, Han) | (Katakana, Han) | (Katakana, Hiragana) | (Han, Hangul) | (Hangul, Han)
+        )
+    }
+}
+
+/// A report on the contents of a corpus.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CorpusStats {
+    /// Number of lines read, including blank ones
+    pub lines: usize,
+
+    /// Lines holding nothing but whitespace.  A line break at the very end of the corpus does not count.
+    pub blank_lines: usize,
+
+    /// Lines with whitespace before or after the word, including a carriage return left by Windows line endings
+    pub padded_lines: usize,
+
+    /// Lines repeating a word from an earlier line
+    pub duplicate_lines: usize,
+
+    /// Number of unique words
+    pub vocabulary_size: usize,
+
+    /// Number of unique words of each length in characters (Unicode scalar values)
+    pub char_lengths: BTreeMap<usize, usize>,
+
+    /// Number of unique words of each length in grapheme clusters
+    pub grapheme_lengths: BTreeMap<usize, usize>,
+
+    /// Every character used in a word
+    pub characters: BTreeSet<char>,
+
+    /// Number of unique words using each script.  A word using two scripts counts towards both.
+    pub scripts: BTreeMap<Script, usize>,
+
+    /// Words whose letters come from more than one script, in corpus order
+    pub mixed_script_words: Vec<String>,
+}
+
+impl CorpusStats {
+    /// True if the corpus has no blank, padded or duplicate lines and no words that mix scripts.
+    pub fn is_clean(&self) -> bool {
+        self.blank_lines == 0 && self.padded_lines == 0 && self.duplicate_lines == 0 && self.mixed_script_words.is_empty()
+    }
+
+    /// The mean word length in characters, or 0 for an empty corpus.
+    pub fn mean_char_length(&self) -> f64 {
+        mean(&self.char_lengths)
+    }
+
+    /// The mean word length in grapheme clusters, or 0 for an empty corpus.
+    pub fn mean_grapheme_length(&self) -> f64 {
+        mean(&self.grapheme_lengths)
+    }
+}
+
+fn mean(histogram: &BTreeMap<usize, usize>) -> f64 {
+    let words: usize = histogram.values().sum();
+    if words == 0 {
+        return 0.0;
+    }
+    histogram.iter().map(|(length, n)| length * n).sum::<usize>() as f64 / words as f64
+}
+
+/// Read a corpus and report on its contents.  Trained Markov chains and loaded corpora have already been cleaned, so their line counts only cover their unique words.
+pub fn corpus_stats(corpus: Corpus) -> Result<CorpusStats, GeneratorError> {
+    let mut lines = match corpus.read()? {
+        Contents::Lines(lines, _) => lines,
+        Contents::Vocabulary(vocabulary) => vocabulary.words().to_vec(),
+    };
+    if lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    let mut stats = CorpusStats { lines: lines.len(), ..CorpusStats::default() };
+    let mut seen = BTreeSet::new();
+    for line in &lines {
+        // word lists may carry counts and tags after a tab
+        let word = line.split('\t').next().unwrap_or_default();
+        let trimmed = word.trim();
+        if trimmed.is_empty() {
+            stats.blank_lines += 1;
+            continue;
+        }
+        if trimmed.len() != word.len() || line.trim_end().len() != line.len() {
+            stats.padded_lines += 1;
+        }
+        if !seen.insert(trimmed) {
+            stats.duplicate_lines += 1;
+            continue;
+        }
+
+        *stats.char_lengths.entry(trimmed.chars().count()).or_insert(0) += 1;
+        *stats.grapheme_lengths.entry(trimmed.graphemes(true).count()).or_insert(0) += 1;
+        stats.characters.extend(trimmed.chars());
+
+        let scripts: BTreeSet<Script> = trimmed.chars()
+            .map(Script::of)
+            .filter(|s| !matches!(s, Script::Common | Script::Inherited))
+            .collect();
+        for script in &scripts {
+            *stats.scripts.entry(*script).or_insert(0) += 1;
+        }
+        if scripts.iter().any(|a| scripts.iter().any(|b| a != b && !a.writes_with(*b))) {
+            stats.mixed_script_words.push(trimmed.to_owned());
+        }
+    }
+    stats.vocabulary_size = seen.len();
+
+    Ok(stats)
+}
+
+impl Display for CorpusStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Lines:            {}", self.lines)?;
+        writeln!(f, "Blank lines:      {}", self.blank_lines)?;
+        writeln!(f, "Padded lines:     {}", self.padded_lines)?;
+        writeln!(f, "Duplicate lines:  {}", self.duplicate_lines)?;
+        writeln!(f, "Vocabulary size:  {}", self.vocabulary_size)?;
+        writeln!(f, "Mean length:      {:.2} characters, {:.2} graphemes", self.mean_char_length(), self.mean_grapheme_length())?;
+        writeln!(f, "Distinct characters: {}", self.characters.len())?;
+
+        writeln!(f, "\nWord lengths (graphemes):")?;
+        for (length, n) in &self.grapheme_lengths {
+            writeln!(f, "  {:>3}  {}", length, n)?;
+        }
+
+        writeln!(f, "\nScripts:")?;
+        for (script, n) in &self.scripts {
+            writeln!(f, "  {:?}  {}", script, n)?;
+        }
+
+        write!(f, "\nMixed-script words: {}", self.mixed_script_words.len())?;
+        for word in &self.mixed_script_words {
+            write!(f, "\n  {}", word)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! Endless streams of generated text.  The corpus is read once and text is generated on demand, so any amount of filler can be piped into a file or socket without holding it in memory.
+
+use rand::Rng;
+use std::io::Read;
+
+use super::error::GeneratorError;
+use super::typography::Typography;
+use super::{build_paragraph, make_sentence, load_source, Corpus, ParagraphOptions, Source};
+
+/// An endless iterator over generated sentences.
+pub struct Sentences<R: Rng> {
+    source: Source,
+    typography: Typography,
+    options: ParagraphOptions,
+    rng: R,
+}
+
+/// An endless iterator over generated paragraphs.
+pub struct Paragraphs<R: Rng> {
+    source: Source,
+    typography: Typography,
+    options: ParagraphOptions,
+    rng: R,
+}
+
+/// Generated paragraphs as an endless `std::io::Read`, separated by blank lines.
+/// Use `Read::take` to bound the output.
+pub struct TextReader<R: Rng> {
+    paragraphs: Paragraphs<R>,
+
+    /// The part of the current paragraph that has not been read yet
+    pending: Vec<u8>,
+    position: usize,
+}
+
+/// Read a corpus once and iterate over sentences generated from it.
+pub fn sentences<R: Rng>(corpus: Corpus, options: &ParagraphOptions, rng: R) -> Result<Sentences<R>, GeneratorError> {
+    options.validate()?;
+    let typography = options.typography_for(corpus.language());
+    let source = load_source(corpus, &options.sampling, options.reading_level.as_ref())?;
+
+    Ok(Sentences { source, typography, options: options.clone(), rng })
+}
+
+/// Read a corpus once and iterate over paragraphs generated from it.  Each paragraph follows the given options.
+pub fn paragraphs<R: Rng>(corpus: Corpus, options: &ParagraphOptions, rng: R) -> Result<Paragraphs<R>, GeneratorError> {
+    options.validate()?;
+    let typography = options.typography_for(corpus.language());
+    let source = load_source(corpus, &options.sampling, options.reading_level.as_ref())?;
+
+    Ok(Paragraphs { source, typography, options: options.clone(), rng })
+}
+
+impl<R: Rng> Iterator for Sentences<R> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let words = self.source.sentence_words(&mut self.rng);
+        Some(make_sentence(&words, &self.typography, &self.options, &mut self.rng, true))
+    }
+}
+
+impl<R: Rng> Iterator for Paragraphs<R> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        Some(build_paragraph(&self.source, &self.typography, &self.options, &mut self.rng))
+    }
+}
+
+impl<R: Rng> Paragraphs<R> {
+    /// Turn this iterator into an endless reader.
+    pub fn into_reader(self) -> TextReader<R> {
+        TextReader { paragraphs: self, pending: Vec::new(), position: 0 }
+    }
+}
+
+impl<R: Rng> Read for TextReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.position == self.pending.len() {
+            let paragraph = self.paragraphs.next().unwrap_or_default();
+            self.pending = format!("{}\n\n", paragraph).into_bytes();
+            self.position = 0;
+        }
+
+        let n = buf.len().min(self.pending.len() - self.position);
+        buf[..n].copy_from_slice(&self.pending[self.position..self.position + n]);
+        self.position += n;
+
+        Ok(n)
+    }
+}
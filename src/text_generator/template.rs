@@ -0,0 +1,136 @@
+//! Sentence templates with part-of-speech slots, such as "The {adj} {noun} {verb:past} {adv}."  Slots are filled from a word list whose words carry tags, so the result reads like a grammatical sentence rather than a random bag of words.
+//!
+//! A slot names a tag, and `{verb}` is filled with any form of a verb while `{verb:past}` only takes words tagged "verb:past".  Write `{{` and `}}` for literal braces.
+
+use rand::Rng;
+use std::str::FromStr;
+
+use crate::casing;
+use super::error::GeneratorError;
+use super::typography::{Casing, Typography};
+use super::vocabulary::Vocabulary;
+use super::{random_index, Corpus};
+
+/// A parsed sentence template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    /// Text copied as written
+    Text(String),
+
+    /// A tag to fill with a word
+    Slot(String),
+}
+
+impl Template {
+    /// Parse a template.  Fails on an unclosed or empty slot, or a stray closing brace.
+    pub fn parse(text: &str) -> Result<Template, GeneratorError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                },
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                },
+                '{' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(GeneratorError::InvalidTemplate(format!("unclosed slot in \"{}\"", text))),
+                            Some(c) => tag.push(c)
+                        }
+                    }
+                    let tag = tag.trim();
+                    if tag.is_empty() {
+                        return Err(GeneratorError::InvalidTemplate(format!("empty slot in \"{}\"", text)));
+                    }
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Piece::Slot(tag.to_lowercase()));
+                },
+                '}' => return Err(GeneratorError::InvalidTemplate(format!("unmatched \"}}\" in \"{}\"", text))),
+                c => literal.push(c)
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Text(literal));
+        }
+
+        Ok(Template { pieces })
+    }
+
+    /// The tag of every slot, in order.
+    pub fn slots(&self) -> impl Iterator<Item = &str> {
+        self.pieces.iter().filter_map(|p| match p {
+            Piece::Slot(tag) => Some(tag.as_str()),
+            Piece::Text(_) => None
+        })
+    }
+
+    /// Fill every slot with a random word carrying its tag.
+    pub fn fill<R: Rng + ?Sized>(&self, vocabulary: &Vocabulary, rng: &mut R) -> Result<String, GeneratorError> {
+        let mut sentence = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => sentence.push_str(text),
+                Piece::Slot(tag) => {
+                    let words = vocabulary.with_tag(tag);
+                    if words.is_empty() {
+                        return Err(GeneratorError::UnknownTag(tag.to_owned()));
+                    }
+                    sentence.push_str(words[random_index(rng, words.len())]);
+                }
+            }
+        }
+
+        Ok(sentence)
+    }
+}
+
+impl FromStr for Template {
+    type Err = GeneratorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::parse(s)
+    }
+}
+
+/// Generate `sentences` sentences, each from a randomly chosen template, and join them into a paragraph.  The corpus is read once.
+/// Every slot of every template is checked first, so a tag missing from the corpus is always reported, whichever templates are drawn.
+pub fn generate_from_templates<R: Rng + ?Sized>(corpus: Corpus, templates: &[Template], sentences: usize, rng: &mut R) -> Result<String, GeneratorError> {
+    if templates.is_empty() {
+        return Err(GeneratorError::InvalidTemplate(String::from("no templates to choose from")));
+    }
+
+    let vocabulary = corpus.load()?;
+    if let Some(tag) = templates.iter().flat_map(|t| t.slots()).find(|tag| vocabulary.with_tag(tag).is_empty()) {
+        return Err(GeneratorError::UnknownTag(tag.to_owned()));
+    }
+    let typography = vocabulary.language().map_or_else(Typography::latin, |l| l.typography());
+
+    let mut paragraph = Vec::new();
+    for _ in 0..sentences {
+        let template = &templates[random_index(rng, templates.len())];
+        let mut sentence = template.fill(&vocabulary, rng)?;
+        if typography.casing != Casing::None {
+            sentence = casing::capitalize_first(&sentence, typography.case_locale);
+        }
+        paragraph.push(sentence);
+    }
+
+    Ok(paragraph.join(typography.sentence_separator))
+}
@@ -0,0 +1,264 @@
+//! Adversarial text for testing rendering and storage.  A paragraph is generated from a corpus as usual, then hazards such as combining-mark stacks, zero-width joiners, bidi controls, emoji sequences, astral-plane characters, normalization variants and lone surrogates are injected into some of its words.  Every insertion is reported with its byte range, so tests can check that each hazard survived a round trip.
+
+use rand::Rng;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::language::Language;
+use super::typography::Direction;
+use super::vocabulary::Vocabulary;
+use super::{generate_paragraph_with_rng, probability, random_index, seeded_rng, Corpus, ParagraphOptions};
+
+/// A kind of text that is known to break software.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hazard {
+    /// A letter carrying a stack of combining marks
+    CombiningStack,
+
+    /// A zero-width joiner, non-joiner, space or word joiner inside a word
+    ZeroWidth,
+
+    /// Directional overrides, embeddings or isolates around a run mixing Arabic and Latin words
+    BidiControl,
+
+    /// An emoji ZWJ sequence, such as a family or a person with a skin tone
+    EmojiSequence,
+
+    /// A character outside the Basic Multilingual Plane, written as a surrogate pair in UTF-16
+    AstralPlane,
+
+    /// A word switched between its composed (NFC) and decomposed (NFD) forms.  Only words whose two forms differ can get this hazard.
+    Normalization,
+
+    /// An unpaired UTF-16 surrogate.  Rust strings cannot hold one, so the text carries U+FFFD in its place and the surrogate only appears, escaped, in [TortureText::to_json_string].
+    LoneSurrogate,
+}
+
+impl Hazard {
+    /// Every hazard.
+    pub const ALL: [Hazard; 7] = [
+        Hazard::CombiningStack,
+        Hazard::ZeroWidth,
+        Hazard::BidiControl,
+        Hazard::EmojiSequence,
+        Hazard::AstralPlane,
+        Hazard::Normalization,
+        Hazard::LoneSurrogate,
+    ];
+}
+
+const ZERO_WIDTH: [char; 4] = ['\u{200D}', '\u{200C}', '\u{200B}', '\u{2060}'];
+
+/// Opening and closing controls: overrides and embeddings end with PDF, isolates with PDI.
+const BIDI_CONTROLS: [(char, char); 6] = [
+    ('\u{202E}', '\u{202C}'),
+    ('\u{202D}', '\u{202C}'),
+    ('\u{202B}', '\u{202C}'),
+    ('\u{2067}', '\u{2069}'),
+    ('\u{2066}', '\u{2069}'),
+    ('\u{2068}', '\u{2069}'),
+];
+
+const EMOJI_SEQUENCES: [&str; 7] = [
+    "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}",
+    "\u{1F469}\u{1F3FD}\u{200D}\u{1F4BB}",
+    "\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}",
+    "\u{1F9D1}\u{1F3FF}\u{200D}\u{1F91D}\u{200D}\u{1F9D1}\u{1F3FB}",
+    "\u{1F441}\u{FE0F}\u{200D}\u{1F5E8}\u{FE0F}",
+    "\u{1F43B}\u{200D}\u{2744}\u{FE0F}",
+    "\u{1F3F4}\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}",
+];
+
+/// Mathematical letters, historic scripts, a CJK Extension B ideograph and emoji.
+const ASTRAL: [char; 8] = ['\u{1D504}', '\u{1D4B3}', '\u{10348}', '\u{13000}', '\u{2070E}', '\u{1F600}', '\u{1F4A9}', '\u{1F9EA}'];
+
+/// Options for torture text.
+#[derive(Clone, Debug)]
+pub struct TortureOptions {
+    /// Chance, from 0 to 1, that a word gets a hazard
+    rate: f64,
+
+    /// The hazards to choose from
+    hazards: Vec<Hazard>,
+
+    /// Options for the paragraph the hazards are injected into
+    paragraph: ParagraphOptions,
+}
+
+impl Default for TortureOptions {
+    fn default() -> Self {
+        TortureOptions { rate: 0.1, hazards: Hazard::ALL.to_vec(), paragraph: ParagraphOptions::default() }
+    }
+}
+
+impl TortureOptions {
+    /// Every hazard, in one word out of ten.
+    pub fn new() -> TortureOptions {
+        TortureOptions::default()
+    }
+
+    /// Set the chance, from 0 to 1, that a word gets a hazard.  NaN counts as 0.
+    pub fn set_rate(mut self, rate: f64) -> TortureOptions {
+        self.rate = probability(rate);
+        self
+    }
+
+    /// Set the hazards to choose from.
+    pub fn set_hazards(mut self, hazards: &[Hazard]) -> TortureOptions {
+        self.hazards = hazards.to_vec();
+        self
+    }
+
+    /// Set the options for the underlying paragraph.
+    pub fn set_paragraph_options(mut self, options: ParagraphOptions) -> TortureOptions {
+        self.paragraph = options;
+        self
+    }
+}
+
+/// One injected hazard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Insertion {
+    pub hazard: Hazard,
+
+    /// Byte offset in the text where the affected word, with anything inserted around it, starts
+    pub start: usize,
+
+    /// Byte offset just past the affected word and anything inserted around it
+    pub end: usize,
+}
+
+/// A paragraph with hazards injected, and a record of each one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TortureText {
+    pub text: String,
+
+    /// Every hazard injected, in text order
+    pub insertions: Vec<Insertion>,
+
+    /// The byte offset of each U+FFFD standing in for a lone surrogate, and the surrogate it stands for
+    surrogates: Vec<(usize, u16)>,
+}
+
+impl TortureText {
+    /// How many times each hazard was injected.
+    pub fn counts(&self) -> BTreeMap<Hazard, usize> {
+        let mut counts = BTreeMap::new();
+        for insertion in &self.insertions {
+            *counts.entry(insertion.hazard).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The text as a quoted JSON string, with each lone surrogate written as a `\uXXXX` escape.  Strict JSON parsers reject such strings, which is the point of the hazard.
+    pub fn to_json_string(&self) -> String {
+        let mut json = String::from("\"");
+        let mut from = 0;
+        for &(offset, unit) in &self.surrogates {
+            json.push_str(&escape(&self.text[from..offset]));
+            json.push_str(&format!("\\u{:04x}", unit));
+            from = offset + '\u{FFFD}'.len_utf8();
+        }
+        json.push_str(&escape(&self.text[from..]));
+        json.push('"');
+        json
+    }
+}
+
+/// A piece of text escaped for a JSON string, without the quotes.
+fn escape(text: &str) -> String {
+    let quoted = serde_json::Value::from(text).to_string();
+    quoted[1..quoted.len() - 1].to_owned()
+}
+
+/// Generate a paragraph and inject hazards into its words, drawing all randomness from the caller's random number generator.
+/// Bidi hazards mix in words from the bundled Arabic corpus, or from the English one when the text is already right-to-left.
+pub fn generate_torture<R: Rng + ?Sized>(corpus: Corpus, options: &TortureOptions, rng: &mut R) -> Result<TortureText, GeneratorError> {
+    let typography = options.paragraph.typography_for(corpus.language());
+    let paragraph = generate_paragraph_with_rng(corpus, &options.paragraph, rng)?;
+    let other = match typography.direction {
+        Direction::Rtl => Vocabulary::embedded(Language::En),
+        Direction::Ltr => Vocabulary::embedded(Language::Ar),
+    };
+
+    let mut torture = TortureText { text: String::new(), insertions: Vec::new(), surrogates: Vec::new() };
+    for segment in paragraph.split_word_bounds() {
+        let is_word = segment.chars().any(char::is_alphanumeric);
+        if !is_word || options.hazards.is_empty() || !rng.gen_bool(options.rate) {
+            torture.text.push_str(segment);
+            continue;
+        }
+
+        // words without distinct composed and decomposed forms cannot get a normalization hazard
+        let applicable: Vec<Hazard> = options.hazards.iter()
+            .copied()
+            .filter(|h| *h != Hazard::Normalization || normalization_variant(segment).is_some())
+            .collect();
+        if applicable.is_empty() {
+            torture.text.push_str(segment);
+            continue;
+        }
+
+        let hazard = applicable[random_index(rng, applicable.len())];
+        let start = torture.text.len();
+        let at = grapheme_boundary(segment, rng);
+        let (head, tail) = segment.split_at(at);
+        let word = match hazard {
+            Hazard::CombiningStack => {
+                let marks: String = (0..3 + random_index(rng, 6))
+                    .map(|_| char::from_u32(0x300 + random_index(rng, 0x70) as u32).unwrap_or('\u{301}'))
+                    .collect();
+                format!("{}{}{}", head, marks, tail)
+            },
+            Hazard::ZeroWidth => format!("{}{}{}", head, ZERO_WIDTH[random_index(rng, ZERO_WIDTH.len())], tail),
+            Hazard::BidiControl => {
+                let (open, close) = BIDI_CONTROLS[random_index(rng, BIDI_CONTROLS.len())];
+                let foreign = &other.words()[random_index(rng, other.len())];
+                format!("{}{}{}{}{}", open, segment, typography.word_separator, foreign, close)
+            },
+            Hazard::EmojiSequence => format!("{}{}{}", segment, typography.word_separator, EMOJI_SEQUENCES[random_index(rng, EMOJI_SEQUENCES.len())]),
+            Hazard::AstralPlane => format!("{}{}{}", head, ASTRAL[random_index(rng, ASTRAL.len())], tail),
+            Hazard::Normalization => normalization_variant(segment).unwrap_or_else(|| segment.to_owned()),
+            Hazard::LoneSurrogate => {
+                let unit = 0xD800 + random_index(rng, 0x800) as u16;
+                torture.surrogates.push((start + head.len(), unit));
+                format!("{}\u{FFFD}{}", head, tail)
+            },
+        };
+
+        torture.text.push_str(&word);
+        torture.insertions.push(Insertion { hazard, start, end: torture.text.len() });
+    }
+
+    Ok(torture)
+}
+
+/// Generate torture text from a seed.  The same seed, corpus and options always produce the same text and hazards.
+pub fn generate_torture_from_seed(corpus: Corpus, options: &TortureOptions, seed: u64) -> Result<TortureText, GeneratorError> {
+    generate_torture(corpus, options, &mut seeded_rng(seed))
+}
+
+/// The decomposed form of a composed word, or the composed form of a decomposed one.  None if both forms are the same.
+fn normalization_variant(word: &str) -> Option<String> {
+    let decomposed: String = word.nfd().collect();
+    if decomposed != word {
+        return Some(decomposed);
+    }
+    let composed: String = word.nfc().collect();
+    if composed != word {
+        return Some(composed);
+    }
+    None
+}
+
+/// A random byte offset between two grapheme clusters of a word, never at its start.
+fn grapheme_boundary<R: Rng + ?Sized>(word: &str, rng: &mut R) -> usize {
+    let boundaries: Vec<usize> = word.grapheme_indices(true)
+        .map(|(i, g)| i + g.len())
+        .collect();
+    boundaries[random_index(rng, boundaries.len())]
+}
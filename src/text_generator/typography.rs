@@ -0,0 +1,182 @@
+//! Typographic conventions used when words are assembled into sentences.  Each bundled language has its own settings, so generated text uses the right separators, punctuation, casing and direction for its script.
+
+use serde::Serialize;
+
+use crate::casing::CaseLocale;
+use super::language::Language;
+
+/// The direction a script is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Direction {
+    /// Left to right
+    Ltr,
+
+    /// Right to left
+    Rtl,
+}
+
+/// How the words of a sentence are cased.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Casing {
+    /// Lowercase every word, then capitalize the first letter of the sentence.  Case changes follow the full Unicode rules of the language.
+    Sentence,
+
+    /// Keep the case of each word as it appears in the corpus, and capitalize the first letter of the sentence.  Used for languages like German where nouns are always capitalized.
+    CapitalizeFirst,
+
+    /// Leave the text alone.  Used for scripts without letter case.
+    None,
+}
+
+/// Separators, punctuation, casing and direction for one language.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Typography {
+    /// Placed between the words of a sentence
+    pub word_separator: &'static str,
+
+    /// Placed between the sentences of a paragraph
+    pub sentence_separator: &'static str,
+
+    /// Ends a statement
+    pub full_stop: &'static str,
+
+    /// Ends a question
+    pub question_mark: &'static str,
+
+    /// Ends an exclamation
+    pub exclamation_mark: &'static str,
+
+    /// Separates clauses within a sentence
+    pub comma: &'static str,
+
+    /// Separates closely related clauses
+    pub semicolon: &'static str,
+
+    /// Open and close quoted speech
+    pub quotes: (&'static str, &'static str),
+
+    /// Open and close a parenthetical aside
+    pub parentheses: (&'static str, &'static str),
+
+    /// Whether questions and exclamations also open with an inverted mark, as in Spanish "¿" and "¡"
+    pub inverted_marks: bool,
+
+    /// Placed before question marks, exclamation marks and semicolons, as French does with a narrow no-break space
+    pub punctuation_space: &'static str,
+
+    /// How sentences are cased
+    pub casing: Casing,
+
+    /// Language-specific casing rules, such as the Turkish dotted and dotless i
+    pub case_locale: CaseLocale,
+
+    /// The direction the script is written in
+    pub direction: Direction,
+}
+
+impl Typography {
+    /// Space-separated words and Latin punctuation, as used by English.
+    pub fn latin() -> Typography {
+        Typography {
+            word_separator: " ",
+            sentence_separator: " ",
+            full_stop: ".",
+            question_mark: "?",
+            exclamation_mark: "!",
+            comma: ",",
+            semicolon: ";",
+            quotes: ("“", "”"),
+            parentheses: ("(", ")"),
+            inverted_marks: false,
+            punctuation_space: "",
+            casing: Casing::Sentence,
+            case_locale: CaseLocale::Root,
+            direction: Direction::Ltr,
+        }
+    }
+
+    /// French uses guillemets and a narrow no-break space before high punctuation.
+    pub fn french() -> Typography {
+        Typography {
+            quotes: ("«\u{a0}", "\u{a0}»"),
+            punctuation_space: "\u{202f}",
+            ..Typography::latin()
+        }
+    }
+
+    /// Spanish opens questions and exclamations with inverted marks and quotes with guillemets.
+    pub fn spanish() -> Typography {
+        Typography {
+            quotes: ("«", "»"),
+            inverted_marks: true,
+            ..Typography::latin()
+        }
+    }
+
+    /// Arabic punctuation, no letter case, written right to left.
+    pub fn arabic() -> Typography {
+        Typography {
+            question_mark: "؟",
+            comma: "،",
+            semicolon: "؛",
+            quotes: ("«", "»"),
+            casing: Casing::None,
+            direction: Direction::Rtl,
+            ..Typography::latin()
+        }
+    }
+
+    /// Devanagari sentences end with a danda and have no letter case.
+    pub fn devanagari() -> Typography {
+        Typography {
+            full_stop: "।",
+            casing: Casing::None,
+            ..Typography::latin()
+        }
+    }
+
+    /// Japanese is written without spaces and uses full-width punctuation.
+    pub fn japanese() -> Typography {
+        Typography {
+            word_separator: "",
+            sentence_separator: "",
+            full_stop: "。",
+            question_mark: "？",
+            exclamation_mark: "！",
+            comma: "、",
+            semicolon: "；",
+            quotes: ("「", "」"),
+            parentheses: ("（", "）"),
+            inverted_marks: false,
+            punctuation_space: "",
+            casing: Casing::None,
+            case_locale: CaseLocale::Root,
+            direction: Direction::Ltr,
+        }
+    }
+}
+
+impl Default for Typography {
+    fn default() -> Self {
+        Typography::latin()
+    }
+}
+
+impl Language {
+    /// The typographic conventions of this language.
+    pub fn typography(&self) -> Typography {
+        let typography = match self {
+            Language::Ar => Typography::arabic(),
+            Language::Cs => Typography { quotes: ("„", "“"), ..Typography::latin() },
+            Language::De => Typography { quotes: ("„", "“"), casing: Casing::CapitalizeFirst, ..Typography::latin() },
+            Language::Es => Typography::spanish(),
+            Language::Fr => Typography::french(),
+            Language::Hi => Typography::devanagari(),
+            Language::Ja => Typography::japanese(),
+            Language::Ru => Typography { quotes: ("«", "»"), ..Typography::latin() },
+            Language::Sv => Typography { quotes: ("”", "”"), ..Typography::latin() },
+            _ => Typography::latin(),
+        };
+        Typography { case_locale: CaseLocale::from(*self), ..typography }
+    }
+}
@@ -0,0 +1,196 @@
+//! A corpus read and preprocessed once.  A Vocabulary holds trimmed, deduplicated words indexed by length and part-of-speech tag, and is reference counted, so it can be cloned cheaply and shared across threads.  Generating from a loaded Vocabulary does no I/O.
+//!
+//! Each line of a word list holds a word, optionally followed by tab-separated columns.  A numeric column is the word's frequency and any other column is a comma-separated list of tags, e.g. "walked\tverb:past" or "run\tnoun,verb\t1200".
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::error::GeneratorError;
+use super::language::Language;
+use super::markov::MarkovChain;
+
+/// The preprocessed words of a corpus.  Create one with `Corpus::load`, then generate from it with `Corpus::Loaded`.
+#[derive(Clone)]
+pub struct Vocabulary {
+    inner: Arc<Entries>,
+}
+
+struct Entries {
+    /// Unique words in corpus order, so rank-based sampling still follows the order of the word list
+    words: Vec<String>,
+
+    /// The frequency of each word, if every line of the word list had one.  Counts of duplicate lines are added up.
+    counts: Option<Vec<u64>>,
+
+    /// Indices into `words`, keyed by length in grapheme clusters
+    by_length: BTreeMap<usize, Vec<usize>>,
+
+    /// Indices into `words`, keyed by lowercase tag
+    by_tag: BTreeMap<String, Vec<usize>>,
+
+    /// The chain sentences come from, for a corpus trained on running text
+    chain: Option<MarkovChain>,
+
+    language: Option<Language>,
+}
+
+/// Bundled corpora are only split and indexed the first time they are used.
+static EMBEDDED: [OnceLock<Vocabulary>; Language::ALL.len()] = [const { OnceLock::new() }; Language::ALL.len()];
+
+impl Vocabulary {
+    /// Build a vocabulary from the lines of a word list.  Lines are trimmed, blank lines are dropped and repeated words are kept once, with their counts added up and their tags merged.
+    pub(crate) fn from_lines(lines: Vec<String>, language: Option<Language>) -> Result<Vocabulary, GeneratorError> {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut words: Vec<String> = Vec::new();
+        let mut counts: Vec<u64> = Vec::new();
+        let mut tags: Vec<Vec<String>> = Vec::new();
+        let mut every_line_counted = true;
+
+        for line in &lines {
+            let (word, count, line_tags) = parse_line(line);
+            if word.is_empty() {
+                continue;
+            }
+            every_line_counted &= count.is_some();
+
+            let i = match seen.get(word) {
+                Some(&i) => i,
+                None => {
+                    seen.insert(word.to_owned(), words.len());
+                    words.push(word.to_owned());
+                    counts.push(0);
+                    tags.push(Vec::new());
+                    words.len() - 1
+                }
+            };
+            counts[i] += count.unwrap_or(0);
+            for tag in line_tags {
+                if !tags[i].contains(&tag) {
+                    tags[i].push(tag);
+                }
+            }
+        }
+
+        if words.is_empty() {
+            return Err(GeneratorError::EmptyCorpus);
+        }
+
+        let counts = if every_line_counted { Some(counts) } else { None };
+        Ok(Vocabulary::build(words, counts, tags, None, language))
+    }
+
+    /// Build a vocabulary whose sentences come from a trained chain.  Its words are every word the chain can produce.
+    pub(crate) fn from_chain(chain: MarkovChain) -> Vocabulary {
+        let words = chain.words();
+        let tags = vec![Vec::new(); words.len()];
+        Vocabulary::build(words, None, tags, Some(chain), None)
+    }
+
+    /// The vocabulary of a bundled corpus, loaded on first use and shared from then on.
+    pub(crate) fn embedded(language: Language) -> Vocabulary {
+        EMBEDDED[language as usize]
+            .get_or_init(|| {
+                let lines = language.corpus().split('\n').map(|l| l.to_owned()).collect();
+                Vocabulary::from_lines(lines, Some(language)).expect("bundled corpora hold words")
+            })
+            .clone()
+    }
+
+    fn build(words: Vec<String>, counts: Option<Vec<u64>>, tags: Vec<Vec<String>>, chain: Option<MarkovChain>, language: Option<Language>) -> Vocabulary {
+        let mut by_length: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (i, word) in words.iter().enumerate() {
+            by_length.entry(word.graphemes(true).count()).or_default().push(i);
+        }
+
+        let mut by_tag: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, word_tags) in tags.into_iter().enumerate() {
+            for tag in word_tags {
+                by_tag.entry(tag).or_default().push(i);
+            }
+        }
+
+        Vocabulary { inner: Arc::new(Entries { words, counts, by_length, by_tag, chain, language }) }
+    }
+
+    /// The unique words of the corpus, in the order they first appear.
+    pub fn words(&self) -> &[String] {
+        &self.inner.words
+    }
+
+    /// The number of unique words.
+    pub fn len(&self) -> usize {
+        self.inner.words.len()
+    }
+
+    /// True if the vocabulary holds no words.  Only a Markov chain trained on text without words can be empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.words.is_empty()
+    }
+
+    /// The language of the bundled corpus this vocabulary was loaded from, if any.
+    pub fn language(&self) -> Option<Language> {
+        self.inner.language
+    }
+
+    /// The words that are exactly `length` grapheme clusters long.
+    pub fn with_length(&self, length: usize) -> impl Iterator<Item = &str> {
+        self.inner.by_length
+            .get(&length)
+            .into_iter()
+            .flatten()
+            .map(|&i| self.inner.words[i].as_str())
+    }
+
+    /// Every word length in the vocabulary, from shortest to longest, in grapheme clusters.
+    pub fn lengths(&self) -> impl Iterator<Item = usize> + '_ {
+        self.inner.by_length.keys().copied()
+    }
+
+    /// Every tag in the vocabulary, sorted.
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.inner.by_tag.keys().map(|t| t.as_str())
+    }
+
+    /// The words tagged `tag` or any form of it, so "verb" also finds words tagged "verb:past".  Tags are compared without regard to case.
+    pub fn with_tag(&self, tag: &str) -> Vec<&str> {
+        let tag = tag.to_lowercase();
+        let form = format!("{}:", tag);
+
+        let mut indices: Vec<usize> = self.inner.by_tag
+            .range(tag.clone()..)
+            .take_while(|(t, _)| t.starts_with(&tag))
+            .filter(|(t, _)| **t == tag || t.starts_with(&form))
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        indices.into_iter().map(|i| self.inner.words[i].as_str()).collect()
+    }
+
+    pub(crate) fn counts(&self) -> Option<&[u64]> {
+        self.inner.counts.as_deref()
+    }
+
+    pub(crate) fn chain(&self) -> Option<&MarkovChain> {
+        self.inner.chain.as_ref()
+    }
+}
+
+/// Split a word list line into its word, its count and its lowercase tags.
+fn parse_line(line: &str) -> (&str, Option<u64>, Vec<String>) {
+    let mut columns = line.split('\t');
+    let word = columns.next().unwrap_or_default().trim();
+
+    let mut count = None;
+    let mut tags = Vec::new();
+    for column in columns.map(|c| c.trim()) {
+        match column.parse::<u64>() {
+            Ok(c) => count = Some(c),
+            Err(_) => tags.extend(column.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty())),
+        }
+    }
+
+    (word, count, tags)
+}